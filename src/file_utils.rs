@@ -8,7 +8,6 @@ pub fn read_i32_list(filename: &str) -> Vec<i32> {
 
     BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|line| line.unwrap().trim().parse().unwrap())
         .collect()
 }
@@ -24,7 +23,6 @@ pub fn read_string_list(filename: &str) -> Vec<String> {
 
     BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|line| line.unwrap().trim().to_owned())
         .collect()
 }
@@ -2,14 +2,16 @@ use std::env;
 
 mod file_utils;
 mod puzzles;
+mod rng;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     let default = "all".to_owned();
     let filter = args.get(1).unwrap_or(&default);
+    let puzzle_args = if args.len() > 2 { &args[2..] } else { &[] };
 
-    let puzzles: Vec<fn()> = vec![
+    let puzzles: Vec<fn(&[String])> = vec![
         puzzles::day1::solve,
         puzzles::day2::solve,
         puzzles::day3::solve,
@@ -20,7 +22,7 @@ fn main() {
 
     puzzles.iter().enumerate().for_each(|(i, solve)| {
         if filter == "all" || filter == &(i + 1).to_string() {
-            solve()
+            solve(puzzle_args)
         }
     });
 }
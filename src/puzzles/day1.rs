@@ -1,6 +1,6 @@
 use crate::file_utils;
 
-pub fn solve() {
+pub fn solve(_args: &[String]) {
     let day1_input = file_utils::read_i32_list("data/day1.input");
     println!("Day1 A result: {}", count_increases(&day1_input));
     println!("Day1 B result: {}", count_sliding(&day1_input));
@@ -1,48 +1,377 @@
-use crate::file_utils;
-
-pub fn solve() {
-    let day2_input = read_submarine_commands("data/day2.input");
-    println!("Day2 A Result {}", calc_position_mul(&day2_input));
-    println!("Day2 B Result {}", calc_position_aimed(&day2_input));
-}
-
-fn read_submarine_commands(filename: &str) -> Vec<Command> {
-    file_utils::read_string_list(filename)
-        .into_iter()
-        .map(|line| {
-            let tokens = line.split(' ').collect::<Vec<&str>>();
-            let cmd = tokens[0];
-            let arg = tokens[1];
-
-            match cmd {
-                "forward" => Command::Forward(arg.parse().unwrap()),
-                "down" => Command::Down(arg.parse().unwrap()),
-                "up" => Command::Up(arg.parse().unwrap()),
-                _ => panic!("Unknown cmd: {}", cmd),
+mod constraints;
+/// The commands are parsed with nom, the grammar is a superset of the
+/// puzzle's input format, see the parser module for details.
+mod parser;
+mod planner;
+mod svg;
+mod trace;
+
+use std::fmt;
+
+use crate::file_utils::read_and_parse;
+
+pub fn solve(args: &[String]) {
+    let day2_input = read_and_parse("data/day2.input", parser::parse).unwrap();
+
+    match args {
+        [mode, model] if mode == "model" => match submarine_by_name(model) {
+            Some(submarine) => match run(submarine, &day2_input) {
+                Ok(submarine) => println!(
+                    "Day2 {} result {}",
+                    model,
+                    position_product(submarine.position())
+                ),
+                Err(err) => println!("Day2 {} failed: {:?}", model, err),
+            },
+            None => println!("Unknown submarine model: {}", model),
+        },
+        [mode, model] if mode == "trace" => match submarine_by_name(model) {
+            Some(submarine) => match trace::to_csv(trace::trace(submarine, &day2_input)) {
+                Ok(csv) => print!("{}", csv),
+                Err(err) => println!("Day2 {} failed: {:?}", model, err),
+            },
+            None => println!("Unknown submarine model: {}", model),
+        },
+        [mode, model, limits @ ..] if mode == "check" => match submarine_by_name(model) {
+            Some(submarine) => {
+                let max_depth = match limits
+                    .first()
+                    .map(|max_depth| max_depth.parse())
+                    .transpose()
+                {
+                    Ok(max_depth) => max_depth,
+                    Err(_) => {
+                        return println!("Usage: check <model> [<max depth> [<seabed file>]]")
+                    }
+                };
+                let seabed = match limits.get(1) {
+                    Some(filename) => match read_seabed(filename) {
+                        Some(seabed) => Some(seabed),
+                        None => return println!("Cannot read the seabed from {}", filename),
+                    },
+                    None => None,
+                };
+                let constraints = constraints::Constraints {
+                    max_depth,
+                    seabed,
+                    ..constraints::Constraints::default()
+                };
+
+                match constraints::run_constrained(submarine, &day2_input, &constraints) {
+                    Ok(submarine) => println!(
+                        "Day2 {} stayed within the limits, final position: {:?}",
+                        model,
+                        submarine.position()
+                    ),
+                    Err(violation) => println!(
+                        "Day2 {} violated the limits at step {} ({:?}): {:?} at {:?}",
+                        model,
+                        violation.step,
+                        violation.command,
+                        violation.kind,
+                        violation.position
+                    ),
+                }
+            }
+            None => println!("Unknown submarine model: {}", model),
+        },
+        [mode, model, horizontal, depth] if mode == "plan" => {
+            let target = match (horizontal.parse(), depth.parse()) {
+                (Ok(horizontal), Ok(depth)) => Position { horizontal, depth },
+                _ => return println!("Usage: plan <model> <horizontal> <depth>"),
+            };
+            let plan = match model.as_str() {
+                "buggy" => planner::plan_buggy(target),
+                "aimed" => planner::plan_aimed(target),
+                _ => return println!("Unknown submarine model: {}", model),
+            };
+
+            match plan {
+                Ok(plan) => match serialize(&plan) {
+                    Ok(serialized) => print!("{}", serialized),
+                    Err(err) => println!("Cannot write the route: {:?}", err),
+                },
+                Err(err) => println!("Cannot plan a route: {:?}", err),
+            }
+        }
+        [mode] if mode == "svg" => match svg::render(&day2_input) {
+            Ok(svg) => print!("{}", svg),
+            Err(err) => println!("Day2 failed: {:?}", err),
+        },
+        _ => {
+            match calc_position_mul(&day2_input) {
+                Ok(result) => println!("Day2 A Result {}", result),
+                Err(err) => println!("Day2 A failed: {:?}", err),
+            }
+            match calc_position_aimed(&day2_input) {
+                Ok(result) => println!("Day2 B Result {}", result),
+                Err(err) => println!("Day2 B failed: {:?}", err),
             }
-        })
+        }
+    }
+}
+
+/// Reads a seabed in the day1 format, one depth per line. Unlike the
+/// puzzle inputs, the file comes from the command line, so a missing or
+/// malformed file is reported instead of panicking.
+fn read_seabed(filename: &str) -> Option<Vec<i32>> {
+    std::fs::read_to_string(filename)
+        .ok()?
+        .lines()
+        .map(|line| line.trim().parse().ok())
         .collect()
 }
 
-fn calc_position_mul(cmds: &[Command]) -> i32 {
-    let submarine = cmds.iter().fold(BuggySubmarine::new(), |s, cmd| s.run(cmd));
-    submarine.depth * submarine.horizontal
+fn calc_position_mul(cmds: &[Command]) -> Result<i64, RunError> {
+    run(BuggySubmarine::new(), cmds).map(|submarine| position_product(submarine.position()))
+}
+
+fn calc_position_aimed(cmds: &[Command]) -> Result<i64, RunError> {
+    run(AimedSubmarine::new(), cmds).map(|submarine| position_product(submarine.position()))
+}
+
+/// The product of two `i32`s always fits into an `i64`.
+fn position_product(position: Position) -> i64 {
+    position.horizontal as i64 * position.depth as i64
+}
+
+/// Applies every command in order and returns the moved submarine, or the
+/// index of the first command that failed.
+fn run<S: Submarine>(mut submarine: S, cmds: &[Command]) -> Result<S, RunError> {
+    for (index, cmd) in cmds.iter().enumerate() {
+        submarine
+            .apply(cmd)
+            .map_err(|error| RunError { index, error })?;
+    }
+
+    Ok(submarine)
+}
+
+/// Writes the commands in the format read by the parser, one per line.
+/// Fails on the first command the grammar cannot express.
+fn serialize(cmds: &[Command]) -> Result<String, SerializeError<'_>> {
+    match inexpressible(cmds) {
+        Some(command) => Err(SerializeError { command }),
+        None => Ok(cmds.iter().map(|cmd| format!("{}\n", cmd)).collect()),
+    }
+}
+
+/// A command with a negative amount, or a turn that is not a right angle.
+#[derive(Debug, PartialEq)]
+pub struct SerializeError<'a> {
+    pub command: &'a Command,
+}
+
+fn inexpressible(cmds: &[Command]) -> Option<&Command> {
+    cmds.iter().find_map(|cmd| match cmd {
+        Command::Forward(n) | Command::Back(n) | Command::Up(n) | Command::Down(n) if *n < 0 => {
+            Some(cmd)
+        }
+        Command::Turn(_, degrees) if *degrees < 0 || degrees % 90 != 0 => Some(cmd),
+        Command::Repeat(_, body) => inexpressible(body),
+        _ => None,
+    })
+}
+
+/// Flattens `repeat` blocks, so that only the commands that actually move
+/// the submarine remain, in the order they are executed. The blocks are
+/// walked lazily, so large repeat counts don't need any memory.
+fn expand(cmds: &[Command]) -> Expand<'_> {
+    Expand {
+        stack: vec![(cmds.iter(), cmds, 0)],
+    }
+}
+
+/// Whether running the commands moves the submarine at all, so that blocks
+/// without any movement are skipped instead of being repeated in vain.
+fn has_moves(cmds: &[Command]) -> bool {
+    cmds.iter().any(|cmd| match cmd {
+        Command::Repeat(n, body) => *n > 0 && has_moves(body),
+        _ => true,
+    })
+}
+
+/// The blocks being executed, innermost last, each with the iterator of the
+/// current pass over its body and the number of passes still to come.
+struct Expand<'a> {
+    stack: Vec<(std::slice::Iter<'a, Command>, &'a [Command], u32)>,
+}
+
+impl<'a> Iterator for Expand<'a> {
+    type Item = &'a Command;
+
+    fn next(&mut self) -> Option<&'a Command> {
+        loop {
+            let (pass, body, remaining) = self.stack.last_mut()?;
+
+            match pass.next() {
+                Some(Command::Repeat(n, inner)) => {
+                    if *n > 0 && has_moves(inner) {
+                        self.stack.push((inner.iter(), inner, n - 1));
+                    }
+                }
+                Some(cmd) => return Some(cmd),
+                None if *remaining > 0 => {
+                    *remaining -= 1;
+                    *pass = body.iter();
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
 }
 
-fn calc_position_aimed(cmds: &[Command]) -> i32 {
-    let submarine = cmds.iter().fold(AimedSubmarine::new(), |s, cmd| s.run(cmd));
-    submarine.depth * submarine.horizontal
+/// Looks up a movement model by the name used on the command line.
+fn submarine_by_name(name: &str) -> Option<Box<dyn Submarine>> {
+    match name {
+        "buggy" => Some(Box::new(BuggySubmarine::new())),
+        "aimed" => Some(Box::new(AimedSubmarine::new())),
+        _ => None,
+    }
 }
 
-enum Command {
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
     Forward(i32),
     Up(i32),
     Down(i32),
+    Back(i32),
+    Turn(Turn, i32),
+    SetAim(i32),
+    Repeat(u32, Vec<Command>),
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Forward(n) => write!(f, "forward {}", n),
+            Command::Up(n) => write!(f, "up {}", n),
+            Command::Down(n) => write!(f, "down {}", n),
+            Command::Back(n) => write!(f, "back {}", n),
+            Command::Turn(Turn::Left, degrees) => write!(f, "turn left {}", degrees),
+            Command::Turn(Turn::Right, degrees) => write!(f, "turn right {}", degrees),
+            Command::SetAim(aim) => write!(f, "set aim {}", aim),
+            Command::Repeat(n, body) => {
+                writeln!(f, "repeat {} {{", n)?;
+                for cmd in body {
+                    // nested blocks are indented one more level
+                    for line in cmd.to_string().lines() {
+                        writeln!(f, "  {}", line)?;
+                    }
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Why a submarine could not execute a command.
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    /// The command would move the submarine beyond the range of `i32`.
+    Overflow,
+    /// The movement model gives the command no meaning.
+    Unsupported,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunError {
+    /// Index of the command in the list that was run. A failure inside a
+    /// `repeat` block reports the index of the block.
+    pub index: usize,
+    pub error: CommandError,
+}
+
+fn checked(value: Option<i32>) -> Result<i32, CommandError> {
+    value.ok_or(CommandError::Overflow)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Turn {
+    /// Returns the new heading in degrees, normalised to `0..360`.
+    fn apply(self, heading: i32, degrees: i32) -> i32 {
+        match self {
+            Turn::Left => (heading - degrees.rem_euclid(360)).rem_euclid(360),
+            Turn::Right => (heading + degrees.rem_euclid(360)).rem_euclid(360),
+        }
+    }
+}
+
+/// How much of a move along `heading` ends up on the horizontal axis. The
+/// parser only accepts turns by multiples of 90 degrees, so moving sideways
+/// leaves the horizontal position unchanged.
+fn horizontal_factor(heading: i32) -> i32 {
+    match heading {
+        0 => 1,
+        180 => -1,
+        _ => 0,
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    pub horizontal: i32,
+    pub depth: i32,
+}
+
+/// A movement model: the same command list can be interpreted differently
+/// depending on how the submarine reacts to each command.
+///
+/// A command that fails leaves the submarine unchanged.
+pub trait Submarine {
+    fn apply(&mut self, cmd: &Command) -> Result<(), CommandError>;
+    fn position(&self) -> Position;
+
+    /// Only models that track an aim separately from the depth have one.
+    fn aim(&self) -> Option<i32> {
+        None
+    }
+}
+
+/// Runs the body of a `repeat` block `n` times. A later pass could still
+/// fail, so the passes run on a copy that replaces the submarine only once
+/// all of them succeeded.
+fn apply_repeat<S: Submarine + Clone>(
+    submarine: &mut S,
+    n: u32,
+    body: &[Command],
+) -> Result<(), CommandError> {
+    let mut repeated = submarine.clone();
+    for _ in 0..n {
+        for cmd in body {
+            repeated.apply(cmd)?;
+        }
+    }
+    *submarine = repeated;
+
+    Ok(())
+}
+
+impl<S: Submarine + ?Sized> Submarine for Box<S> {
+    fn apply(&mut self, cmd: &Command) -> Result<(), CommandError> {
+        (**self).apply(cmd)
+    }
+
+    fn position(&self) -> Position {
+        (**self).position()
+    }
+
+    fn aim(&self) -> Option<i32> {
+        (**self).aim()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct AimedSubmarine {
     pub aim: i32,
+    pub heading: i32,
     pub horizontal: i32,
     pub depth: i32,
 }
@@ -51,28 +380,55 @@ impl AimedSubmarine {
     fn new() -> AimedSubmarine {
         AimedSubmarine {
             aim: 0,
+            heading: 0,
             horizontal: 0,
             depth: 0,
         }
     }
 
-    fn run(self, cmd: &Command) -> AimedSubmarine {
+    fn advance(&mut self, distance: i32) -> Result<(), CommandError> {
+        let horizontal = checked(
+            horizontal_factor(self.heading)
+                .checked_mul(distance)
+                .and_then(|h| h.checked_add(self.horizontal)),
+        )?;
+        let depth = checked(
+            self.aim
+                .checked_mul(distance)
+                .and_then(|d| d.checked_add(self.depth)),
+        )?;
+
+        self.horizontal = horizontal;
+        self.depth = depth;
+        Ok(())
+    }
+}
+
+impl Submarine for AimedSubmarine {
+    fn apply(&mut self, cmd: &Command) -> Result<(), CommandError> {
         match cmd {
-            Command::Forward(h) => AimedSubmarine {
-                horizontal: self.horizontal + h,
-                depth: self.depth + (self.aim * h),
-                ..self
-            },
-            Command::Up(d) => AimedSubmarine {
-                aim: self.aim - d,
-                ..self
-            },
-            Command::Down(d) => AimedSubmarine {
-                aim: self.aim + d,
-                ..self
-            },
+            Command::Forward(h) => self.advance(*h)?,
+            Command::Back(h) => self.advance(checked(h.checked_neg())?)?,
+            Command::Up(d) => self.aim = checked(self.aim.checked_sub(*d))?,
+            Command::Down(d) => self.aim = checked(self.aim.checked_add(*d))?,
+            Command::Turn(turn, degrees) => self.heading = turn.apply(self.heading, *degrees),
+            Command::SetAim(aim) => self.aim = *aim,
+            Command::Repeat(n, body) => apply_repeat(self, *n, body)?,
+        }
+
+        Ok(())
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            horizontal: self.horizontal,
+            depth: self.depth,
         }
     }
+
+    fn aim(&self) -> Option<i32> {
+        Some(self.aim)
+    }
 }
 
 #[cfg(test)]
@@ -92,15 +448,18 @@ mod aimed_submarine_tests {
             Command::Forward(2),
         ];
 
-        let moved = cmds.iter().fold(sub, |sub, cmd| sub.run(cmd));
+        let moved = run(sub, &cmds).unwrap();
 
         assert_eq!(moved.horizontal, 15);
         assert_eq!(moved.depth, 60);
     }
 }
 
-#[derive(Debug)]
+/// Interprets `up` and `down` as direct depth changes. There is no aim, so
+/// `set aim` is not supported.
+#[derive(Debug, Clone)]
 pub struct BuggySubmarine {
+    pub heading: i32,
     pub horizontal: i32,
     pub depth: i32,
 }
@@ -108,25 +467,315 @@ pub struct BuggySubmarine {
 impl BuggySubmarine {
     pub fn new() -> BuggySubmarine {
         BuggySubmarine {
+            heading: 0,
             horizontal: 0,
             depth: 0,
         }
     }
+}
+
+impl Submarine for BuggySubmarine {
+    fn apply(&mut self, cmd: &Command) -> Result<(), CommandError> {
+        let factor = horizontal_factor(self.heading);
 
-    fn run(self, cmd: &Command) -> BuggySubmarine {
         match cmd {
-            Command::Forward(h) => BuggySubmarine {
-                horizontal: self.horizontal + h,
-                depth: self.depth,
-            },
-            Command::Up(d) => BuggySubmarine {
-                horizontal: self.horizontal,
-                depth: self.depth - d,
-            },
-            Command::Down(d) => BuggySubmarine {
-                horizontal: self.horizontal,
-                depth: self.depth + d,
-            },
+            Command::Forward(h) => {
+                self.horizontal = checked(
+                    factor
+                        .checked_mul(*h)
+                        .and_then(|h| self.horizontal.checked_add(h)),
+                )?
+            }
+            Command::Back(h) => {
+                self.horizontal = checked(
+                    factor
+                        .checked_mul(*h)
+                        .and_then(|h| self.horizontal.checked_sub(h)),
+                )?
+            }
+            Command::Up(d) => self.depth = checked(self.depth.checked_sub(*d))?,
+            Command::Down(d) => self.depth = checked(self.depth.checked_add(*d))?,
+            Command::Turn(turn, degrees) => self.heading = turn.apply(self.heading, *degrees),
+            Command::SetAim(_) => return Err(CommandError::Unsupported),
+            Command::Repeat(n, body) => apply_repeat(self, *n, body)?,
+        }
+
+        Ok(())
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            horizontal: self.horizontal,
+            depth: self.depth,
         }
     }
 }
+
+/// The example of the puzzle description, shared by the tests of the
+/// submodules.
+#[cfg(test)]
+fn example() -> Vec<Command> {
+    vec![
+        Command::Forward(5),
+        Command::Down(5),
+        Command::Forward(8),
+        Command::Up(3),
+        Command::Down(8),
+        Command::Forward(2),
+    ]
+}
+
+#[cfg(test)]
+mod submarine_tests {
+    use super::*;
+
+    #[test]
+    fn test_buggy_pos() {
+        assert_eq!(
+            run(BuggySubmarine::new(), &example()).unwrap().position(),
+            Position {
+                horizontal: 15,
+                depth: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_submarine_by_name() {
+        let aimed = submarine_by_name("aimed").unwrap();
+        let buggy = submarine_by_name("buggy").unwrap();
+
+        assert_eq!(run(aimed, &example()).unwrap().position().depth, 60);
+        assert_eq!(run(buggy, &example()).unwrap().position().depth, 10);
+        assert!(submarine_by_name("sideways").is_none());
+    }
+
+    #[test]
+    fn test_repeat_is_the_same_as_expanded_commands() {
+        let repeated = [
+            Command::Repeat(2, vec![Command::Down(1), Command::Forward(3)]),
+            Command::Forward(1),
+        ];
+        let expanded = [
+            Command::Down(1),
+            Command::Forward(3),
+            Command::Down(1),
+            Command::Forward(3),
+            Command::Forward(1),
+        ];
+
+        assert_eq!(
+            run(AimedSubmarine::new(), &repeated).unwrap().position(),
+            run(AimedSubmarine::new(), &expanded).unwrap().position()
+        );
+        assert_eq!(
+            run(BuggySubmarine::new(), &repeated).unwrap().position(),
+            run(BuggySubmarine::new(), &expanded).unwrap().position()
+        );
+    }
+
+    #[test]
+    fn test_turning_around_and_back() {
+        let cmds = [
+            Command::Down(2),
+            Command::Forward(5),
+            Command::Turn(Turn::Right, 180),
+            Command::Forward(2),
+            Command::Back(1),
+            Command::Turn(Turn::Left, 90),
+            Command::Forward(10),
+        ];
+
+        assert_eq!(
+            run(AimedSubmarine::new(), &cmds).unwrap().position(),
+            Position {
+                horizontal: 4,
+                depth: 32
+            }
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        let cmds = [
+            Command::Repeat(
+                2,
+                vec![
+                    Command::Forward(1),
+                    Command::Repeat(2, vec![Command::Up(1)]),
+                ],
+            ),
+            Command::Down(3),
+        ];
+
+        assert_eq!(
+            expand(&cmds).collect::<Vec<_>>(),
+            vec![
+                &Command::Forward(1),
+                &Command::Up(1),
+                &Command::Up(1),
+                &Command::Forward(1),
+                &Command::Up(1),
+                &Command::Up(1),
+                &Command::Down(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_is_lazy() {
+        let cmds = [
+            Command::Repeat(u32::MAX, vec![Command::Repeat(0, vec![])]),
+            Command::Repeat(u32::MAX, vec![Command::Forward(1), Command::Up(1)]),
+        ];
+
+        assert_eq!(
+            expand(&cmds).take(3).collect::<Vec<_>>(),
+            vec![&Command::Forward(1), &Command::Up(1), &Command::Forward(1)]
+        );
+    }
+
+    #[test]
+    fn test_run_reports_overflowing_command() {
+        let cmds = [
+            Command::Forward(1),
+            Command::Down(i32::MAX),
+            Command::Forward(2),
+            Command::Forward(3),
+        ];
+
+        assert_eq!(
+            run(AimedSubmarine::new(), &cmds).map(|_| ()),
+            Err(RunError {
+                index: 2,
+                error: CommandError::Overflow
+            })
+        );
+        assert!(run(BuggySubmarine::new(), &cmds).is_ok());
+        assert_eq!(
+            run(
+                BuggySubmarine::new(),
+                &[Command::Down(i32::MAX), Command::Down(1)]
+            )
+            .map(|_| ()),
+            Err(RunError {
+                index: 1,
+                error: CommandError::Overflow
+            })
+        );
+    }
+
+    #[test]
+    fn test_moving_backwards_by_the_smallest_amount_overflows() {
+        let cmds = [Command::Turn(Turn::Right, 180), Command::Forward(i32::MIN)];
+        let overflow = Err(RunError {
+            index: 1,
+            error: CommandError::Overflow,
+        });
+
+        assert_eq!(run(AimedSubmarine::new(), &cmds).map(|_| ()), overflow);
+        assert_eq!(run(BuggySubmarine::new(), &cmds).map(|_| ()), overflow);
+    }
+
+    #[test]
+    fn test_failed_command_leaves_submarine_unchanged() {
+        let mut submarine = AimedSubmarine::new();
+        submarine.apply(&Command::Down(2)).unwrap();
+        submarine.apply(&Command::Forward(1)).unwrap();
+
+        assert_eq!(
+            submarine.apply(&Command::Forward(i32::MAX)),
+            Err(CommandError::Overflow)
+        );
+        assert_eq!(
+            (submarine.horizontal, submarine.depth, submarine.aim),
+            (1, 2, 2)
+        );
+
+        let repeat = Command::Repeat(2, vec![Command::Down(1), Command::Forward(i32::MAX)]);
+        assert_eq!(submarine.apply(&repeat), Err(CommandError::Overflow));
+        assert_eq!(
+            (submarine.horizontal, submarine.depth, submarine.aim),
+            (1, 2, 2)
+        );
+
+        let mut buggy = BuggySubmarine::new();
+        let repeat = Command::Repeat(2, vec![Command::Forward(1), Command::SetAim(1)]);
+        assert_eq!(buggy.apply(&repeat), Err(CommandError::Unsupported));
+        assert_eq!(buggy.horizontal, 0);
+    }
+
+    #[test]
+    fn test_answers_do_not_overflow() {
+        let cmds = [
+            Command::Down(20_000),
+            Command::Forward(100_000),
+            Command::Forward(1),
+        ];
+
+        assert_eq!(calc_position_aimed(&cmds), Ok(100_001 * 2_000_020_000));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let cmds = [
+            Command::Forward(5),
+            Command::Repeat(
+                2,
+                vec![
+                    Command::Turn(Turn::Left, 90),
+                    Command::Repeat(3, vec![Command::Back(1)]),
+                ],
+            ),
+            Command::SetAim(-2),
+        ];
+
+        assert_eq!(
+            serialize(&cmds).unwrap(),
+            "forward 5
+repeat 2 {
+  turn left 90
+  repeat 3 {
+    back 1
+  }
+}
+set aim -2
+"
+        );
+    }
+
+    #[test]
+    fn test_serialize_rejects_what_cannot_be_parsed() {
+        let turn = [Command::Repeat(2, vec![Command::Turn(Turn::Right, 45)])];
+
+        assert_eq!(
+            serialize(&[Command::Forward(1), Command::Up(-3)]),
+            Err(SerializeError {
+                command: &Command::Up(-3)
+            })
+        );
+        assert_eq!(
+            serialize(&turn),
+            Err(SerializeError {
+                command: &Command::Turn(Turn::Right, 45)
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_aim() {
+        let cmds = [Command::Down(5), Command::SetAim(-1), Command::Forward(3)];
+
+        assert_eq!(
+            run(AimedSubmarine::new(), &cmds).unwrap().position().depth,
+            -3
+        );
+        assert_eq!(
+            run(BuggySubmarine::new(), &cmds).map(|_| ()),
+            Err(RunError {
+                index: 1,
+                error: CommandError::Unsupported
+            })
+        );
+    }
+}
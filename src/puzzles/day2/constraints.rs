@@ -0,0 +1,237 @@
+use super::{expand, Command, CommandError, Position, Submarine};
+
+/// Limits the submarine has to stay within. Depth grows downwards, so the
+/// surface is the smallest allowed depth. By default the surface is at 0.
+#[derive(Debug, PartialEq, Default)]
+pub struct Constraints {
+    pub surface: i32,
+    pub max_depth: Option<i32>,
+    /// Depth of the seabed at each horizontal position, in the same format
+    /// as the day1 sonar sweep. Positions outside of it are not checked.
+    pub seabed: Option<Vec<i32>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ViolationKind {
+    AboveSurface,
+    BelowMaxDepth,
+    HitSeabed { seabed: i32 },
+    Failed(CommandError),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Violation<'a> {
+    /// Index of the command in the expanded command list, as in the trace.
+    pub step: usize,
+    pub command: &'a Command,
+    pub position: Position,
+    pub kind: ViolationKind,
+}
+
+impl Constraints {
+    fn check_point(&self, position: Position) -> Option<ViolationKind> {
+        if position.depth < self.surface {
+            return Some(ViolationKind::AboveSurface);
+        }
+
+        if matches!(self.max_depth, Some(max_depth) if position.depth > max_depth) {
+            return Some(ViolationKind::BelowMaxDepth);
+        }
+
+        let seabed = usize::try_from(position.horizontal)
+            .ok()
+            .and_then(|x| self.seabed.as_ref()?.get(x).copied());
+
+        match seabed {
+            Some(seabed) if position.depth > seabed => Some(ViolationKind::HitSeabed { seabed }),
+            _ => None,
+        }
+    }
+
+    /// Both movement models move along a straight line during a single
+    /// command, so every horizontal position covered by the seabed is checked
+    /// on the way, not only the end of the move. The surface and the max
+    /// depth are flat, so for them the end of the move is enough.
+    fn check_move(&self, from: Position, to: Position) -> Option<(Position, ViolationKind)> {
+        let dx = (to.horizontal as i64 - from.horizontal as i64).signum();
+        let distance = (to.horizontal as i64 - from.horizontal as i64).abs();
+        let seabed_len = self.seabed.as_ref().map_or(0, |seabed| seabed.len() as i64);
+
+        // steps i with 0 <= from.horizontal + dx * i < seabed_len
+        let (first, last) = if dx < 0 {
+            (
+                from.horizontal as i64 - seabed_len + 1,
+                from.horizontal as i64,
+            )
+        } else {
+            (
+                -from.horizontal as i64,
+                seabed_len - 1 - from.horizontal as i64,
+            )
+        };
+
+        (first.max(1)..=last.min(distance))
+            .map(|i| Position {
+                horizontal: (from.horizontal as i64 + dx * i) as i32,
+                depth: (from.depth as i64 + (to.depth as i64 - from.depth as i64) * i / distance)
+                    as i32,
+            })
+            .chain(std::iter::once(to))
+            .find_map(|position| self.check_point(position).map(|kind| (position, kind)))
+    }
+}
+
+/// Runs the commands like `run`, but stops at the first command that takes
+/// the submarine outside of the constraints.
+pub fn run_constrained<'a, S: Submarine>(
+    mut submarine: S,
+    cmds: &'a [Command],
+    constraints: &Constraints,
+) -> Result<S, Violation<'a>> {
+    for (step, command) in expand(cmds).enumerate() {
+        let from = submarine.position();
+
+        if let Err(error) = submarine.apply(command) {
+            return Err(Violation {
+                step,
+                command,
+                position: from,
+                kind: ViolationKind::Failed(error),
+            });
+        }
+
+        if let Some((position, kind)) = constraints.check_move(from, submarine.position()) {
+            return Err(Violation {
+                step,
+                command,
+                position,
+                kind,
+            });
+        }
+    }
+
+    Ok(submarine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{example, AimedSubmarine, BuggySubmarine};
+    use super::*;
+
+    #[test]
+    fn test_run_constrained_without_violation() {
+        let submarine =
+            run_constrained(AimedSubmarine::new(), &example(), &Constraints::default()).unwrap();
+
+        assert_eq!((submarine.horizontal, submarine.depth), (15, 60));
+    }
+
+    #[test]
+    fn test_run_constrained_above_surface() {
+        let cmds = [Command::Down(2), Command::Up(3), Command::Forward(1)];
+
+        assert_eq!(
+            run_constrained(BuggySubmarine::new(), &cmds, &Constraints::default()).map(|_| ()),
+            Err(Violation {
+                step: 1,
+                command: &Command::Up(3),
+                position: Position {
+                    horizontal: 0,
+                    depth: -1
+                },
+                kind: ViolationKind::AboveSurface
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_constrained_max_depth() {
+        let constraints = Constraints {
+            max_depth: Some(50),
+            ..Constraints::default()
+        };
+        let cmds = example();
+
+        assert_eq!(
+            run_constrained(AimedSubmarine::new(), &cmds, &constraints)
+                .map(|_| ())
+                .unwrap_err()
+                .step,
+            5
+        );
+    }
+
+    #[test]
+    fn test_run_constrained_hits_seabed_during_move() {
+        let constraints = Constraints {
+            seabed: Some(vec![10, 10, 10, 4, 10, 10]),
+            ..Constraints::default()
+        };
+        let cmds = [Command::Down(2), Command::Forward(5)];
+
+        assert_eq!(
+            run_constrained(AimedSubmarine::new(), &cmds, &constraints).map(|_| ()),
+            Err(Violation {
+                step: 1,
+                command: &Command::Forward(5),
+                position: Position {
+                    horizontal: 3,
+                    depth: 6
+                },
+                kind: ViolationKind::HitSeabed { seabed: 4 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_constrained_long_moves_outside_the_seabed() {
+        let constraints = Constraints {
+            max_depth: Some(10),
+            seabed: Some(vec![10, 10, 10, 4, 10, 10]),
+            ..Constraints::default()
+        };
+        let cmds = [
+            Command::Forward(2_000_000_000),
+            Command::Back(2_000_000_000),
+            Command::Back(2_000_000_000),
+            Command::Down(20),
+        ];
+
+        assert_eq!(
+            run_constrained(BuggySubmarine::new(), &cmds, &constraints).map(|_| ()),
+            Err(Violation {
+                step: 3,
+                command: &Command::Down(20),
+                position: Position {
+                    horizontal: -2_000_000_000,
+                    depth: 20
+                },
+                kind: ViolationKind::BelowMaxDepth
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_constrained_hits_seabed_moving_back() {
+        let constraints = Constraints {
+            seabed: Some(vec![10, 10, 10, 4, 10, 10]),
+            ..Constraints::default()
+        };
+        let cmds = [
+            Command::Forward(2_000_000_000),
+            Command::Down(5),
+            Command::Back(2_000_000_000),
+        ];
+
+        assert_eq!(
+            run_constrained(BuggySubmarine::new(), &cmds, &constraints)
+                .map(|_| ())
+                .unwrap_err()
+                .position,
+            Position {
+                horizontal: 3,
+                depth: 5
+            }
+        );
+    }
+}
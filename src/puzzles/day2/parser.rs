@@ -0,0 +1,273 @@
+//! Grammar of the command files:
+//!
+//! ```text
+//! program  := command*
+//! command  := "forward" n | "back" n | "up" n | "down" n
+//!           | "turn" ("left" | "right") degrees
+//!           | "set" "aim" signed
+//!           | "repeat" n "{" command* "}"
+//! ```
+//!
+//! Commands and their tokens are separated by whitespace, only the braces of
+//! a `repeat` need none around them. `degrees` has to be a multiple of 90.
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, i32 as signed_i32, multispace0, multispace1},
+    combinator::{eof, map, map_res, peek, value, verify},
+    multi::many0,
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+use super::{Command, Turn};
+
+#[derive(Debug, PartialEq)]
+pub struct ParserError {
+    message: String,
+}
+
+impl ParserError {
+    fn new(message: String) -> ParserError {
+        ParserError { message }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Command>, ParserError> {
+    match terminated(commands, multispace0)(input) {
+        Ok(("", cmds)) => Ok(cmds),
+        Ok((remaining, _)) => Err(ParserError::new(format!("Remaining input: {}", remaining))),
+        Err(err) => Err(ParserError::new(format!("Unexpected error: {:?}", err))),
+    }
+}
+
+fn commands(input: &str) -> IResult<&str, Vec<Command>> {
+    many0(preceded(multispace0, command))(input)
+}
+
+fn command(input: &str) -> IResult<&str, Command> {
+    alt((
+        terminated(
+            alt((
+                map(keyword_arg("forward", amount), Command::Forward),
+                map(keyword_arg("back", amount), Command::Back),
+                map(keyword_arg("up", amount), Command::Up),
+                map(keyword_arg("down", amount), Command::Down),
+                turn,
+                set_aim,
+            )),
+            peek(alt((multispace1, tag("}"), eof))),
+        ),
+        repeat,
+    ))(input)
+}
+
+fn keyword_arg<'a, O>(
+    keyword: &'static str,
+    arg: fn(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    preceded(terminated(tag(keyword), multispace1), arg)
+}
+
+fn amount(input: &str) -> IResult<&str, i32> {
+    map_res(digit1, |n: &str| n.parse())(input)
+}
+
+fn turn(input: &str) -> IResult<&str, Command> {
+    map(
+        keyword_arg("turn", |input| {
+            separated_pair(
+                alt((
+                    value(Turn::Left, tag("left")),
+                    value(Turn::Right, tag("right")),
+                )),
+                multispace1,
+                verify(amount, |degrees| degrees % 90 == 0),
+            )(input)
+        }),
+        |(turn, degrees)| Command::Turn(turn, degrees),
+    )(input)
+}
+
+fn set_aim(input: &str) -> IResult<&str, Command> {
+    map(
+        preceded(
+            tuple((tag("set"), multispace1, tag("aim"), multispace1)),
+            signed_i32,
+        ),
+        Command::SetAim,
+    )(input)
+}
+
+fn repeat(input: &str) -> IResult<&str, Command> {
+    map(
+        keyword_arg("repeat", |input| {
+            separated_pair(
+                map_res(digit1, |n: &str| n.parse()),
+                multispace0,
+                delimited(char('{'), commands, preceded(multispace0, char('}'))),
+            )(input)
+        }),
+        |(n, body)| Command::Repeat(n, body),
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::serialize;
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Commands that can be expressed in the grammar: amounts are never
+    /// negative and turns are right angles.
+    fn arb_command() -> impl Strategy<Value = Command> {
+        let amount = 0..=i32::MAX;
+        let leaf = prop_oneof![
+            amount.clone().prop_map(Command::Forward),
+            amount.clone().prop_map(Command::Back),
+            amount.clone().prop_map(Command::Up),
+            amount.prop_map(Command::Down),
+            (
+                prop_oneof![Just(Turn::Left), Just(Turn::Right)],
+                (0..=i32::MAX / 90).prop_map(|n| n * 90)
+            )
+                .prop_map(|(turn, degrees)| Command::Turn(turn, degrees)),
+            any::<i32>().prop_map(Command::SetAim),
+        ];
+
+        leaf.prop_recursive(3, 32, 8, |inner| {
+            (any::<u32>(), prop::collection::vec(inner, 0..8))
+                .prop_map(|(n, body)| Command::Repeat(n, body))
+        })
+    }
+
+    /// Any command, including the ones `serialize` rejects.
+    fn any_command() -> impl Strategy<Value = Command> {
+        let leaf = prop_oneof![
+            any::<i32>().prop_map(Command::Forward),
+            any::<i32>().prop_map(Command::Back),
+            any::<i32>().prop_map(Command::Up),
+            any::<i32>().prop_map(Command::Down),
+            (
+                prop_oneof![Just(Turn::Left), Just(Turn::Right)],
+                prop_oneof![any::<i32>(), (-4..=4).prop_map(|n| n * 90)]
+            )
+                .prop_map(|(turn, degrees)| Command::Turn(turn, degrees)),
+            any::<i32>().prop_map(Command::SetAim),
+        ];
+
+        leaf.prop_recursive(3, 32, 8, |inner| {
+            (any::<u32>(), prop::collection::vec(inner, 0..8))
+                .prop_map(|(n, body)| Command::Repeat(n, body))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_serialize_round_trip(cmds in prop::collection::vec(arb_command(), 0..32)) {
+            prop_assert_eq!(parse(&serialize(&cmds).unwrap()), Ok(cmds));
+        }
+
+        #[test]
+        fn test_serialized_commands_parse_back(cmds in prop::collection::vec(any_command(), 0..32)) {
+            if let Ok(serialized) = serialize(&cmds) {
+                prop_assert_eq!(parse(&serialized), Ok(cmds));
+            }
+        }
+    }
+
+    #[test]
+    fn test_command() {
+        assert_eq!(command("forward 5"), Ok(("", Command::Forward(5))));
+        assert_eq!(command("back 3"), Ok(("", Command::Back(3))));
+        assert_eq!(command("up 12"), Ok(("", Command::Up(12))));
+        assert_eq!(command("down 7"), Ok(("", Command::Down(7))));
+        assert_eq!(
+            command("turn left 90"),
+            Ok(("", Command::Turn(Turn::Left, 90)))
+        );
+        assert_eq!(command("set aim -4"), Ok(("", Command::SetAim(-4))));
+        assert_eq!(command("set  aim 3"), Ok(("", Command::SetAim(3))));
+        assert_eq!(command("set\taim\n3"), Ok(("", Command::SetAim(3))));
+    }
+
+    #[test]
+    fn test_turn_rejects_non_right_angles() {
+        assert!(command("turn right 45").is_err());
+    }
+
+    #[test]
+    fn test_parse_puzzle_format() {
+        let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
+        assert_eq!(
+            parse(input),
+            Ok(vec![
+                Command::Forward(5),
+                Command::Down(5),
+                Command::Forward(8),
+                Command::Up(3),
+                Command::Down(8),
+                Command::Forward(2),
+            ])
+        )
+    }
+
+    #[test]
+    fn test_parse_nested_repeat() {
+        let input = "set aim 1
+repeat 2 {
+  forward 1
+  repeat 3 { down 1 }
+}
+turn right 180
+back 4
+";
+
+        assert_eq!(
+            parse(input),
+            Ok(vec![
+                Command::SetAim(1),
+                Command::Repeat(
+                    2,
+                    vec![
+                        Command::Forward(1),
+                        Command::Repeat(3, vec![Command::Down(1)])
+                    ]
+                ),
+                Command::Turn(Turn::Right, 180),
+                Command::Back(4),
+            ])
+        )
+    }
+
+    #[test]
+    fn test_parse_fails_on_unknown_command() {
+        assert!(parse("forward 5\nsideways 3\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_fails_on_unseparated_commands() {
+        assert!(parse("forward 5forward 3\ndown 2up 1\n").is_err());
+        assert!(parse("set aim -1up 1").is_err());
+        assert_eq!(
+            parse("repeat 2 {up 1}down 3"),
+            Ok(vec![
+                Command::Repeat(2, vec![Command::Up(1)]),
+                Command::Down(3)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_fails_on_unclosed_repeat() {
+        assert!(parse("repeat 2 {\nforward 1\n").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Builds the shortest command list that moves a submarine to a given
+//! position. Plans only use the commands of the original puzzle input
+//! (`forward`, `up` and `down` with positive amounts), so they can be used
+//! as puzzle inputs directly.
+
+use super::{run, AimedSubmarine, BuggySubmarine, Command, Position, RunError, Submarine};
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    Unreachable(Position),
+    /// The plan did not end up at the target, this is a bug in the planner.
+    Verification {
+        target: Position,
+        reached: Position,
+    },
+    Run(RunError),
+}
+
+pub fn plan_aimed(target: Position) -> Result<Vec<Command>, PlanError> {
+    let Position { horizontal, depth } = target;
+    let unreachable = || PlanError::Unreachable(target);
+
+    let plan = match (horizontal, depth) {
+        (h, _) if h < 0 => return Err(unreachable()),
+        (0, 0) => vec![],
+        // the depth only changes while moving forward
+        (0, _) => return Err(unreachable()),
+        (h, 0) => vec![Command::Forward(h)],
+        (h, d) if d % h == 0 => [aim_change(d / h), vec![Command::Forward(h)]].concat(),
+        // any h >= 2 can be split into a level part and a last step of 1
+        (h, d) => [
+            vec![Command::Forward(h - 1)],
+            aim_change(d),
+            vec![Command::Forward(1)],
+        ]
+        .concat(),
+    };
+
+    verify(AimedSubmarine::new(), plan, target)
+}
+
+pub fn plan_buggy(target: Position) -> Result<Vec<Command>, PlanError> {
+    if target.horizontal < 0 {
+        return Err(PlanError::Unreachable(target));
+    }
+
+    let mut plan = vec![];
+
+    if target.horizontal != 0 {
+        plan.push(Command::Forward(target.horizontal));
+    }
+
+    if target.depth != 0 {
+        plan.extend(aim_change(target.depth));
+    }
+
+    verify(BuggySubmarine::new(), plan, target)
+}
+
+/// A single `up` or `down`, except for `i32::MIN`: its amount does not fit
+/// in an `up`, so it is split in two.
+fn aim_change(by: i32) -> Vec<Command> {
+    match by {
+        i32::MIN => vec![Command::Up(i32::MAX), Command::Up(1)],
+        by if by < 0 => vec![Command::Up(-by)],
+        by => vec![Command::Down(by)],
+    }
+}
+
+fn verify<S: Submarine>(
+    submarine: S,
+    plan: Vec<Command>,
+    target: Position,
+) -> Result<Vec<Command>, PlanError> {
+    let reached = run(submarine, &plan).map_err(PlanError::Run)?.position();
+
+    if reached == target {
+        Ok(plan)
+    } else {
+        Err(PlanError::Verification { target, reached })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(horizontal: i32, depth: i32) -> Position {
+        Position { horizontal, depth }
+    }
+
+    /// Every command list of the given length with amounts up to `max`.
+    fn all_plans(length: usize, max: i32) -> Vec<Vec<Command>> {
+        (0..length).fold(vec![vec![]], |plans, _| {
+            plans
+                .iter()
+                .flat_map(|plan| {
+                    (1..=max).flat_map(move |n| {
+                        [Command::Forward(n), Command::Up(n), Command::Down(n)]
+                            .into_iter()
+                            .map(move |cmd| {
+                                let mut plan = plan.clone();
+                                plan.push(cmd);
+                                plan
+                            })
+                    })
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_plan_aimed_example_target() {
+        let plan = plan_aimed(position(15, 60)).unwrap();
+
+        assert_eq!(plan, vec![Command::Down(4), Command::Forward(15)]);
+    }
+
+    #[test]
+    fn test_plan_aimed_not_divisible() {
+        let plan = plan_aimed(position(4, -7)).unwrap();
+
+        assert_eq!(
+            plan,
+            vec![Command::Forward(3), Command::Up(7), Command::Forward(1)]
+        );
+    }
+
+    #[test]
+    fn test_plan_unreachable() {
+        assert_eq!(
+            plan_aimed(position(0, 5)),
+            Err(PlanError::Unreachable(position(0, 5)))
+        );
+        assert_eq!(
+            plan_buggy(position(-1, 5)),
+            Err(PlanError::Unreachable(position(-1, 5)))
+        );
+    }
+
+    #[test]
+    fn test_plan_buggy() {
+        assert_eq!(
+            plan_buggy(position(15, 10)),
+            Ok(vec![Command::Forward(15), Command::Down(10)])
+        );
+        assert_eq!(plan_buggy(position(0, -3)), Ok(vec![Command::Up(3)]));
+        assert_eq!(plan_buggy(position(0, 0)), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_plan_smallest_depth() {
+        assert_eq!(
+            plan_buggy(position(5, i32::MIN)),
+            Ok(vec![
+                Command::Forward(5),
+                Command::Up(i32::MAX),
+                Command::Up(1)
+            ])
+        );
+        assert_eq!(
+            plan_aimed(position(1, i32::MIN)),
+            Ok(vec![
+                Command::Up(i32::MAX),
+                Command::Up(1),
+                Command::Forward(1)
+            ])
+        );
+        assert!(plan_aimed(position(3, i32::MIN)).is_ok());
+    }
+
+    #[test]
+    fn test_plans_are_minimal() {
+        let shorter_plans: Vec<Vec<Command>> = (0..3).flat_map(|l| all_plans(l, 12)).collect();
+
+        for h in 0..=6 {
+            for d in -6..=6 {
+                let target = position(h, d);
+
+                for (planned, model) in
+                    [(plan_aimed(target), "aimed"), (plan_buggy(target), "buggy")]
+                {
+                    let Ok(planned) = planned else { continue };
+
+                    let shortest = shorter_plans
+                        .iter()
+                        .filter(|plan| {
+                            let reached = match model {
+                                "aimed" => run(AimedSubmarine::new(), plan).map(|s| s.position()),
+                                _ => run(BuggySubmarine::new(), plan).map(|s| s.position()),
+                            };
+                            reached == Ok(target)
+                        })
+                        .map(|plan| plan.len())
+                        .min()
+                        .unwrap_or(3);
+
+                    assert_eq!(planned.len(), shortest, "{} {:?}", model, target);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::{
+    trace::{trace, StepError},
+    AimedSubmarine, BuggySubmarine, Command, Position, Submarine,
+};
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 600.0;
+const MARGIN: f64 = 40.0;
+
+type Route = (&'static str, &'static str, Vec<Position>);
+
+/// The models plotted on top of each other, with their line colours.
+fn routes(cmds: &[Command]) -> Result<Vec<Route>, StepError> {
+    Ok(vec![
+        ("buggy", "#d62728", route(BuggySubmarine::new(), cmds)?),
+        ("aimed", "#1f77b4", route(AimedSubmarine::new(), cmds)?),
+    ])
+}
+
+fn route<S: Submarine>(submarine: S, cmds: &[Command]) -> Result<Vec<Position>, StepError> {
+    let start = submarine.position();
+
+    std::iter::once(Ok(start))
+        .chain(trace(submarine, cmds).map(|step| {
+            step.map(|step| Position {
+                horizontal: step.horizontal,
+                depth: step.depth,
+            })
+        }))
+        .collect()
+}
+
+/// Renders the course of both movement models as an SVG, horizontal
+/// position to the right and depth downwards. Both routes share the same
+/// scale, so the difference between the two interpretations stays visible.
+pub fn render(cmds: &[Command]) -> Result<String, StepError> {
+    let routes = routes(cmds)?;
+    let points = routes.iter().flat_map(|(_, _, route)| route);
+
+    let (min_h, max_h, min_d, max_d) =
+        points.fold((0, 0, 0, 0), |(min_h, max_h, min_d, max_d), position| {
+            (
+                min_h.min(position.horizontal),
+                max_h.max(position.horizontal),
+                min_d.min(position.depth),
+                max_d.max(position.depth),
+            )
+        });
+
+    let scale = |value: i32, min: i32, max: i32, size: f64| {
+        let span = (max as f64 - min as f64).max(1.0);
+        MARGIN + (value as f64 - min as f64) * (size - 2.0 * MARGIN) / span
+    };
+    let surface = scale(0, min_d, max_d, HEIGHT);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <line x1=\"0\" y1=\"{s:.2}\" x2=\"{w}\" y2=\"{s:.2}\" stroke=\"#aaaaaa\" stroke-dasharray=\"4\"/>\n\
+         <text x=\"{w_half}\" y=\"{label_y}\" text-anchor=\"middle\">horizontal ({min_h}..{max_h})</text>\n\
+         <text x=\"12\" y=\"{h_half}\" transform=\"rotate(-90 12 {h_half})\" text-anchor=\"middle\">depth ({min_d}..{max_d})</text>\n",
+        w = WIDTH,
+        h = HEIGHT,
+        s = surface,
+        w_half = WIDTH / 2.0,
+        h_half = HEIGHT / 2.0,
+        label_y = HEIGHT - 10.0,
+        min_h = min_h,
+        max_h = max_h,
+        min_d = min_d,
+        max_d = max_d,
+    );
+
+    for (i, (name, colour, route)) in routes.iter().enumerate() {
+        let points: Vec<String> = route
+            .iter()
+            .map(|position| {
+                format!(
+                    "{:.2},{:.2}",
+                    scale(position.horizontal, min_h, max_h, WIDTH),
+                    scale(position.depth, min_d, max_d, HEIGHT)
+                )
+            })
+            .collect();
+
+        svg += &format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n\
+             <text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+            points.join(" "),
+            colour,
+            WIDTH - MARGIN - 60.0,
+            MARGIN + 20.0 * i as f64,
+            colour,
+            name
+        );
+    }
+
+    Ok(svg + "</svg>\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example;
+    use super::*;
+
+    #[test]
+    fn test_routes_start_at_the_origin() {
+        let routes = routes(&example()).unwrap();
+
+        assert_eq!(routes.len(), 2);
+        for (_, _, route) in routes {
+            assert_eq!(route.len(), 7);
+            assert_eq!(
+                route[0],
+                Position {
+                    horizontal: 0,
+                    depth: 0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_overlays_both_models() {
+        let svg = render(&example()).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        // the aimed route ends at the deepest point, in the bottom right corner
+        assert!(svg.contains("760.00,560.00\" fill=\"none\" stroke=\"#1f77b4\""));
+    }
+}
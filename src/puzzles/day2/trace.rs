@@ -0,0 +1,151 @@
+use super::{expand, Command, CommandError, Submarine};
+
+/// The state of the submarine right after executing a command.
+#[derive(Debug, PartialEq)]
+pub struct TraceStep<'a> {
+    pub step: usize,
+    pub command: &'a Command,
+    pub horizontal: i32,
+    pub depth: i32,
+    pub aim: Option<i32>,
+}
+
+/// A step of the trace failed. Unlike `RunError`, which points into the
+/// list of commands that was run, `step` points into the expanded list.
+#[derive(Debug, PartialEq)]
+pub struct StepError {
+    pub step: usize,
+    pub error: CommandError,
+}
+
+/// Replays the commands one by one, yielding every intermediate state.
+/// `repeat` blocks are expanded, so each step is a single movement. The
+/// trace ends after the first step that fails.
+pub fn trace<'a, S: Submarine + 'a>(
+    mut submarine: S,
+    cmds: &'a [Command],
+) -> impl Iterator<Item = Result<TraceStep<'a>, StepError>> + 'a {
+    let mut failed = false;
+
+    expand(cmds).enumerate().map_while(move |(step, command)| {
+        if failed {
+            return None;
+        }
+
+        if let Err(error) = submarine.apply(command) {
+            failed = true;
+            return Some(Err(StepError { step, error }));
+        }
+
+        let position = submarine.position();
+
+        Some(Ok(TraceStep {
+            step,
+            command,
+            horizontal: position.horizontal,
+            depth: position.depth,
+            aim: submarine.aim(),
+        }))
+    })
+}
+
+pub fn to_csv<'a>(
+    steps: impl IntoIterator<Item = Result<TraceStep<'a>, StepError>>,
+) -> Result<String, StepError> {
+    steps.into_iter().try_fold(
+        "step,command,horizontal,depth,aim\n".to_owned(),
+        |csv, step| {
+            let step = step?;
+
+            Ok(csv
+                + &format!(
+                    "{},{},{},{},{}\n",
+                    step.step,
+                    step.command,
+                    step.horizontal,
+                    step.depth,
+                    step.aim.map(|aim| aim.to_string()).unwrap_or_default()
+                ))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{example, AimedSubmarine, BuggySubmarine};
+    use super::*;
+
+    #[test]
+    fn test_trace_yields_every_state() {
+        let cmds = example();
+        let steps: Vec<TraceStep> = trace(AimedSubmarine::new(), &cmds)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(steps.len(), 6);
+        assert_eq!(
+            steps[2],
+            TraceStep {
+                step: 2,
+                command: &Command::Forward(8),
+                horizontal: 13,
+                depth: 40,
+                aim: Some(5)
+            }
+        );
+        assert_eq!((steps[5].horizontal, steps[5].depth), (15, 60));
+    }
+
+    #[test]
+    fn test_trace_expands_repeat() {
+        let cmds = [Command::Repeat(3, vec![Command::Forward(2)])];
+        let horizontals: Vec<i32> = trace(BuggySubmarine::new(), &cmds)
+            .map(|step| step.unwrap().horizontal)
+            .collect();
+
+        assert_eq!(horizontals, vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn test_trace_does_not_unroll_large_repeats() {
+        let cmds = [Command::Repeat(4_000_000_000, vec![Command::Forward(1)])];
+        let horizontals: Vec<i32> = trace(BuggySubmarine::new(), &cmds)
+            .take(3)
+            .map(|step| step.unwrap().horizontal)
+            .collect();
+
+        assert_eq!(horizontals, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let cmds = example();
+
+        assert_eq!(
+            to_csv(trace(BuggySubmarine::new(), &cmds[..2])),
+            Ok("step,command,horizontal,depth,aim\n0,forward 5,5,0,\n1,down 5,5,5,\n".to_owned())
+        );
+        assert_eq!(
+            to_csv(trace(AimedSubmarine::new(), &cmds[..2])),
+            Ok("step,command,horizontal,depth,aim\n0,forward 5,5,0,0\n1,down 5,5,0,5\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_trace_stops_at_overflow() {
+        let cmds = [
+            Command::Repeat(2, vec![Command::Down(i32::MAX)]),
+            Command::Forward(1),
+        ];
+        let steps: Vec<_> = trace(BuggySubmarine::new(), &cmds).collect();
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(
+            steps[1],
+            Err(StepError {
+                step: 1,
+                error: CommandError::Overflow
+            })
+        );
+    }
+}
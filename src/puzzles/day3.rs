@@ -1,169 +1,372 @@
-use std::isize;
+mod column_counts;
+/// Report lines are stored as bit sets, so the width of the report is not
+/// limited by the size of an integer type.
+mod diagnostic;
+mod report;
+mod trie;
+
+use std::cmp::Ordering;
+use std::fmt;
 
 use crate::file_utils::read_string_list;
 
-pub fn solve() {
+use column_counts::ColumnCounts;
+use diagnostic::Diagnostic;
+use trie::{RatingError, Trie};
+
+pub fn solve(args: &[String]) {
     let diagnostic = read_string_list("data/day3.input");
-    let number_of_bits = diagnostic[0].len();
-    let numeric_diagnostic = parse_str_diagnostic(&diagnostic);
+    let numeric_diagnostic = match parse_str_diagnostic(&diagnostic) {
+        Ok(numeric_diagnostic) => numeric_diagnostic,
+        Err(err) => return println!("Day3 invalid report: {:?}", err),
+    };
+    let number_of_bits = numeric_diagnostic[0].width();
+
+    if args.first().map(String::as_str) == Some("report") {
+        print!(
+            "{}",
+            report::DiagnosticReport::new(&numeric_diagnostic, number_of_bits)
+        );
+        return;
+    }
 
     let (gamma_rate, epsilon_rate) =
         calc_power_consumption_rates(&numeric_diagnostic, number_of_bits);
 
     println!(
         "Day3 A result: gamma_rate: {}, epsilon_rate: {}, power consumption: {}",
-        gamma_rate,
-        epsilon_rate,
-        gamma_rate * epsilon_rate
+        format_rate(&gamma_rate),
+        format_rate(&epsilon_rate),
+        format_product(&gamma_rate, &epsilon_rate)
     );
 
-    let oxygen_generator_rating = calc_oxigen_generator_rating(&numeric_diagnostic, number_of_bits);
-    let co2_scrubber_rating = calc_co2_scrubber_rating(&numeric_diagnostic, number_of_bits);
+    let trie = Trie::build(&numeric_diagnostic, number_of_bits);
+    let oxygen_generator_rating = match calc_oxigen_generator_rating(&trie) {
+        Ok(rating) => rating,
+        Err(err) => return println!("Day3 B no Oxygen Generator rating: {:?}", err),
+    };
+    let co2_scrubber_rating = match calc_co2_scrubber_rating(&trie) {
+        Ok(rating) => rating,
+        Err(err) => return println!("Day3 B no CO2 Scrubber rating: {:?}", err),
+    };
 
     println!(
         "Day3 B result: Oxygen Generator rating: {}, CO2 Scrubber rating: {}, Life Support rating: {}",
-        oxygen_generator_rating,
-        co2_scrubber_rating,
-        oxygen_generator_rating * co2_scrubber_rating
+        format_rate(&oxygen_generator_rating),
+        format_rate(&co2_scrubber_rating),
+        format_product(&oxygen_generator_rating, &co2_scrubber_rating)
     );
 }
 
-fn parse_str_diagnostic(diagnostic: &[String]) -> Vec<isize> {
-    diagnostic
-        .iter()
-        .map(|bin_str| isize::from_str_radix(bin_str, 2).unwrap())
-        .collect()
+/// Rates are printed in decimal when they fit into a `u64`, otherwise in
+/// binary.
+fn format_rate(rate: &Diagnostic) -> String {
+    rate.to_u64()
+        .map(|rate| rate.to_string())
+        .unwrap_or_else(|| format!("0b{}", rate))
 }
 
-fn calc_power_consumption_rates(diagnostic: &[isize], nr_of_bits: usize) -> (i32, i32) {
-    let mut gamma_rate = 0;
-    let mut epsilon_rate = 0;
+fn format_product(a: &Diagnostic, b: &Diagnostic) -> String {
+    match (a.to_u64(), b.to_u64()) {
+        (Some(a), Some(b)) => (a as u128 * b as u128).to_string(),
+        _ => "too wide to multiply".to_owned(),
+    }
+}
+
+/// Line numbers are 1 based, as in an editor.
+#[derive(Debug, PartialEq)]
+pub enum ValidationError {
+    EmptyReport,
+    EmptyLine {
+        line: usize,
+    },
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    WidthMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
 
-    most_common_bits(diagnostic, nr_of_bits)
+/// Every line has to have the width of the first one.
+fn parse_str_diagnostic(diagnostic: &[String]) -> Result<Vec<Diagnostic>, ValidationError> {
+    let mut width = None;
+
+    diagnostic
         .iter()
         .enumerate()
-        .for_each(|(n, &most_comon_bit)| {
-            if most_comon_bit == 1 {
-                gamma_rate |= 1 << n
-            } else {
-                epsilon_rate |= 1 << n
+        .map(|(index, bin_str)| {
+            let line = index + 1;
+
+            if bin_str.is_empty() {
+                return Err(ValidationError::EmptyLine { line });
             }
-        });
 
-    (gamma_rate, epsilon_rate)
-}
+            let parsed = Diagnostic::from_bin_str(bin_str).map_err(|(column, character)| {
+                ValidationError::InvalidCharacter {
+                    line,
+                    column,
+                    character,
+                }
+            })?;
 
-fn calc_oxigen_generator_rating(diagnostic: &[isize], nr_of_bits: usize) -> isize {
-    filter_diagnostic(
-        diagnostic,
-        nr_of_bits,
-        nr_of_bits - 1,
-        |bit, most_common_bit| bit == most_common_bit,
-    )
+            match *width.get_or_insert(parsed.width()) {
+                expected if expected != parsed.width() => Err(ValidationError::WidthMismatch {
+                    line,
+                    expected,
+                    found: parsed.width(),
+                }),
+                _ => Ok(parsed),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|parsed| {
+            if parsed.is_empty() {
+                Err(ValidationError::EmptyReport)
+            } else {
+                Ok(parsed)
+            }
+        })
 }
 
-fn calc_co2_scrubber_rating(diagnostic: &[isize], nr_of_bits: usize) -> isize {
-    filter_diagnostic(
-        diagnostic,
-        nr_of_bits,
-        nr_of_bits - 1,
-        |bit, most_common_bit| bit != most_common_bit,
-    )
+/// A bit value, as selected by a criteria.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bit {
+    Zero,
+    One,
 }
 
-fn filter_diagnostic(
-    diagnostic: &[isize],
-    nr_of_bits: usize,
-    bit: usize,
-    pred: fn(usize, usize) -> bool,
-) -> isize {
-    let most_common_bit = most_common_bit(diagnostic, bit);
+impl Bit {
+    /// The value as an index, e.g. into the children of a trie node.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
 
-    let filtered: Vec<isize> = diagnostic
-        .iter()
-        .filter(|diagnostic| pred(nth_bit(diagnostic, bit), most_common_bit))
-        .cloned()
-        .collect();
-
-    if filtered.len() == 1 {
-        filtered[0]
-    } else {
-        filter_diagnostic(&filtered, nr_of_bits, bit - 1, pred)
+impl fmt::Display for Bit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Bit::Zero => "0",
+            Bit::One => "1",
+        })
     }
 }
 
-fn most_common_bit(diagnostic: &[isize], n_bit: usize) -> usize {
-    let mut frequencies = vec![0; 2];
+/// Which bit value to keep when looking at a column of the report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Commonness {
+    MostCommon,
+    LeastCommon,
+}
+
+/// Bit criteria of the puzzle: the bit value to keep based on how often it
+/// occurs, and the value to keep when zeros and ones are equally common.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitCriteria {
+    pub keep: Commonness,
+    pub tie: Bit,
+}
 
-    diagnostic
-        .iter()
-        .for_each(|diagnostic| frequencies[nth_bit(diagnostic, n_bit)] += 1);
+impl BitCriteria {
+    pub const GAMMA: BitCriteria = BitCriteria {
+        keep: Commonness::MostCommon,
+        tie: Bit::One,
+    };
+    pub const EPSILON: BitCriteria = BitCriteria {
+        keep: Commonness::LeastCommon,
+        tie: Bit::Zero,
+    };
+    pub const OXYGEN_GENERATOR: BitCriteria = BitCriteria::GAMMA;
+    pub const CO2_SCRUBBER: BitCriteria = BitCriteria::EPSILON;
 
-    if frequencies[0] > frequencies[1] {
-        0
-    } else {
-        1
+    pub fn select(&self, zeros: usize, ones: usize) -> Bit {
+        match (self.keep, zeros.cmp(&ones)) {
+            (_, Ordering::Equal) => self.tie,
+            (Commonness::MostCommon, Ordering::Greater)
+            | (Commonness::LeastCommon, Ordering::Less) => Bit::Zero,
+            _ => Bit::One,
+        }
     }
 }
 
-fn most_common_bits(diagnostic: &[isize], nr_of_bits: usize) -> Vec<usize> {
+fn calc_power_consumption_rates(
+    diagnostic: &[Diagnostic],
+    nr_of_bits: usize,
+) -> (Diagnostic, Diagnostic) {
+    let counts = ColumnCounts::count(diagnostic, nr_of_bits);
+
+    (
+        calc_rate(&counts, BitCriteria::GAMMA),
+        calc_rate(&counts, BitCriteria::EPSILON),
+    )
+}
+
+/// Builds a rate from the bit selected by `criteria` in every column.
+fn calc_rate(counts: &ColumnCounts, criteria: BitCriteria) -> Diagnostic {
+    let nr_of_bits = counts.ones.len();
+    let mut rate = Diagnostic::zero(nr_of_bits);
+
     (0..nr_of_bits)
-        .map(|n_bit| most_common_bit(diagnostic, n_bit))
-        .collect()
+        .filter(|&n_bit| criteria.select(counts.zeros(n_bit), counts.ones(n_bit)) == Bit::One)
+        .for_each(|n_bit| rate.set_bit(n_bit));
+
+    rate
+}
+
+fn calc_oxigen_generator_rating(trie: &Trie) -> Result<Diagnostic, RatingError> {
+    trie.rating(BitCriteria::OXYGEN_GENERATOR)
 }
 
-fn nth_bit(x: &isize, n: usize) -> usize {
-    let mask = 1 << n;
-    ((x & mask) >> n).try_into().unwrap()
+fn calc_co2_scrubber_rating(trie: &Trie) -> Result<Diagnostic, RatingError> {
+    trie.rating(BitCriteria::CO2_SCRUBBER)
 }
 
+/// The example report of the puzzle description, shared by the tests of
+/// the submodules.
 #[cfg(test)]
+fn diagnostic() -> Vec<String> {
+    lines(&[
+        "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001",
+        "00010", "01010",
+    ])
+}
 
+#[cfg(test)]
+fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|s| (*s).to_owned()).collect()
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
-    fn diagnostic() -> Vec<String> {
-        vec![
-            "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000",
-            "11001", "00010", "01010",
-        ]
-        .iter()
-        .map(|s| (*s).to_owned())
-        .collect()
-    }
-
     #[test]
     fn test_nth_bit() {
-        diagnostic().iter().for_each(|bin_str| {
-            let n = isize::from_str_radix(bin_str, 2).unwrap();
-            let reconstructed = (0..5).fold("".to_owned(), |acc, index| {
-                nth_bit(&n, index).to_string() + &acc
+        parse_str_diagnostic(&diagnostic())
+            .unwrap()
+            .iter()
+            .zip(diagnostic())
+            .for_each(|(n, bin_str)| {
+                let reconstructed =
+                    (0..5).fold("".to_owned(), |acc, index| n.bit(index).to_string() + &acc);
+                assert_eq!(reconstructed, *bin_str)
             });
-            assert_eq!(reconstructed, *bin_str)
-        });
     }
 
     #[test]
     fn test_calc_power_consumption_rates() {
+        let (gamma_rate, epsilon_rate) =
+            calc_power_consumption_rates(&parse_str_diagnostic(&diagnostic()).unwrap(), 5);
+
         assert_eq!(
-            calc_power_consumption_rates(&parse_str_diagnostic(&diagnostic()), 5),
-            (22, 9)
+            (gamma_rate.to_u64(), epsilon_rate.to_u64()),
+            (Some(22), Some(9))
         );
     }
 
+    #[test]
+    fn test_calc_power_consumption_rates_wide_report() {
+        let wide: Vec<String> = diagnostic()
+            .iter()
+            .map(|bin_str| bin_str.repeat(20))
+            .collect();
+        let (gamma_rate, epsilon_rate) =
+            calc_power_consumption_rates(&parse_str_diagnostic(&wide).unwrap(), 100);
+
+        assert_eq!(gamma_rate.to_string(), "10110".repeat(20));
+        assert_eq!(epsilon_rate.to_string(), "01001".repeat(20));
+    }
+
+    fn trie() -> Trie {
+        Trie::build(&parse_str_diagnostic(&diagnostic()).unwrap(), 5)
+    }
+
     #[test]
     fn test_calc_oxygen_rating() {
         assert_eq!(
-            calc_oxigen_generator_rating(&parse_str_diagnostic(&diagnostic()), 5),
-            23
+            calc_oxigen_generator_rating(&trie()).map(|rating| rating.to_u64()),
+            Ok(Some(23))
         )
     }
 
     #[test]
     fn test_calc_co2_scrubber_rating() {
         assert_eq!(
-            calc_co2_scrubber_rating(&parse_str_diagnostic(&diagnostic()), 5),
-            10
+            calc_co2_scrubber_rating(&trie()).map(|rating| rating.to_u64()),
+            Ok(Some(10))
+        )
+    }
+
+    #[test]
+    fn test_parse_str_diagnostic_errors() {
+        assert_eq!(parse_str_diagnostic(&[]), Err(ValidationError::EmptyReport));
+        assert_eq!(
+            parse_str_diagnostic(&lines(&["0101", "01a1"])),
+            Err(ValidationError::InvalidCharacter {
+                line: 2,
+                column: 3,
+                character: 'a'
+            })
+        );
+        assert_eq!(
+            parse_str_diagnostic(&lines(&["0101", "0111", "011"])),
+            Err(ValidationError::WidthMismatch {
+                line: 3,
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            parse_str_diagnostic(&lines(&["0101", "", "0111"])),
+            Err(ValidationError::EmptyLine { line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_bit_criteria_select() {
+        let least_common_tie_one = BitCriteria {
+            keep: Commonness::LeastCommon,
+            tie: Bit::One,
+        };
+
+        assert_eq!(BitCriteria::GAMMA.select(3, 2), Bit::Zero);
+        assert_eq!(BitCriteria::GAMMA.select(2, 2), Bit::One);
+        assert_eq!(BitCriteria::EPSILON.select(3, 2), Bit::One);
+        assert_eq!(BitCriteria::EPSILON.select(2, 2), Bit::Zero);
+        assert_eq!(least_common_tie_one.select(2, 2), Bit::One);
+        assert_eq!(least_common_tie_one.select(1, 2), Bit::Zero);
+    }
+
+    #[test]
+    fn test_custom_criteria_rating() {
+        // most common bit, but keep zeros on ties
+        let criteria = BitCriteria {
+            keep: Commonness::MostCommon,
+            tie: Bit::Zero,
+        };
+        let diagnostic = parse_str_diagnostic(&lines(&["10", "01"])).unwrap();
+
+        assert_eq!(
+            Trie::build(&diagnostic, 2).rating(criteria),
+            Ok(Diagnostic::from_bin_str("01").unwrap())
+        );
+        assert_eq!(
+            calc_rate(&ColumnCounts::count(&diagnostic, 2), criteria),
+            Diagnostic::from_bin_str("00").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_calc_co2_scrubber_rating_fails_when_filter_empties() {
+        let diagnostic = parse_str_diagnostic(&lines(&["100", "101", "111"])).unwrap();
+
+        assert_eq!(
+            calc_co2_scrubber_rating(&Trie::build(&diagnostic, 3)),
+            Err(RatingError::NoCandidates { bit: 2 })
         )
     }
 }
@@ -0,0 +1,135 @@
+use super::diagnostic::Diagnostic;
+
+/// Number of ones in every column of a report, bit 0 being the rightmost
+/// column.
+#[derive(Debug, PartialEq)]
+pub struct ColumnCounts {
+    pub lines: usize,
+    pub ones: Vec<usize>,
+}
+
+impl ColumnCounts {
+    /// Counts every column in a single pass over the report. The columns of
+    /// each 64 bit word are counted in parallel with bit-sliced counters:
+    /// `planes[k]` holds bit `k` of the counter of all 64 columns, and adding
+    /// a line is a ripple-carry addition over the planes.
+    pub fn count(diagnostic: &[Diagnostic], nr_of_bits: usize) -> ColumnCounts {
+        let mut planes: Vec<Vec<u64>> = vec![vec![]; nr_of_bits.div_ceil(64)];
+
+        for line in diagnostic {
+            for (word_planes, &word) in planes.iter_mut().zip(line.words()) {
+                let mut carry = word;
+
+                for plane in word_planes.iter_mut() {
+                    if carry == 0 {
+                        break;
+                    }
+                    let next_carry = *plane & carry;
+                    *plane ^= carry;
+                    carry = next_carry;
+                }
+
+                if carry != 0 {
+                    word_planes.push(carry);
+                }
+            }
+        }
+
+        let ones = (0..nr_of_bits)
+            .map(|n_bit| {
+                planes[n_bit / 64]
+                    .iter()
+                    .enumerate()
+                    .map(|(k, plane)| (((plane >> (n_bit % 64)) & 1) as usize) << k)
+                    .sum()
+            })
+            .collect();
+
+        ColumnCounts {
+            lines: diagnostic.len(),
+            ones,
+        }
+    }
+
+    pub fn ones(&self, n_bit: usize) -> usize {
+        self.ones[n_bit]
+    }
+
+    pub fn zeros(&self, n_bit: usize) -> usize {
+        self.lines - self.ones[n_bit]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Deterministic report lines, so the tests don't need a random crate.
+    fn generate(lines: usize, width: usize) -> Vec<Diagnostic> {
+        let mut rng = Rng::new(0);
+
+        (0..lines)
+            .map(|_| {
+                let mut line = Diagnostic::zero(width);
+                for n_bit in 0..width {
+                    if rng.next_u64() & 1 == 1 {
+                        line.set_bit(n_bit);
+                    }
+                }
+                line
+            })
+            .collect()
+    }
+
+    fn count_per_column(diagnostic: &[Diagnostic], nr_of_bits: usize) -> Vec<usize> {
+        (0..nr_of_bits)
+            .map(|n_bit| diagnostic.iter().map(|line| line.bit(n_bit)).sum())
+            .collect()
+    }
+
+    #[test]
+    fn test_count() {
+        let diagnostic: Vec<Diagnostic> = ["00100", "11110", "10110", "10111"]
+            .iter()
+            .map(|line| Diagnostic::from_bin_str(line).unwrap())
+            .collect();
+        let counts = ColumnCounts::count(&diagnostic, 5);
+
+        assert_eq!(counts.ones, vec![1, 3, 4, 1, 3]);
+        assert_eq!(counts.zeros(0), 3);
+    }
+
+    #[test]
+    fn test_count_matches_per_column_scan() {
+        let diagnostic = generate(1000, 130);
+
+        assert_eq!(
+            ColumnCounts::count(&diagnostic, 130).ones,
+            count_per_column(&diagnostic, 130)
+        );
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_count_million_lines() {
+        let diagnostic = generate(1_000_000, 12);
+
+        let start = Instant::now();
+        let counts = ColumnCounts::count(&diagnostic, 12);
+        let single_pass = start.elapsed();
+
+        let start = Instant::now();
+        let expected = count_per_column(&diagnostic, 12);
+        let per_column = start.elapsed();
+
+        assert_eq!(counts.ones, expected);
+        println!(
+            "1M lines, 12 bits: single pass {:?}, per column {:?}",
+            single_pass, per_column
+        );
+    }
+}
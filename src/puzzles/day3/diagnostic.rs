@@ -0,0 +1,98 @@
+use std::fmt;
+
+/// A single line of the diagnostic report stored as a bit set, so reports
+/// can be arbitrarily wide. Bit 0 is the rightmost character of the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    words: Vec<u64>,
+    width: usize,
+}
+
+impl Diagnostic {
+    pub fn zero(width: usize) -> Diagnostic {
+        Diagnostic {
+            words: vec![0; width.div_ceil(64)],
+            width,
+        }
+    }
+
+    /// Fails with the (1 based) column and the character that is not a
+    /// binary digit.
+    pub fn from_bin_str(bin_str: &str) -> Result<Diagnostic, (usize, char)> {
+        let width = bin_str.chars().count();
+        let mut diagnostic = Diagnostic::zero(width);
+
+        for (column, c) in bin_str.chars().enumerate() {
+            match c {
+                '0' => (),
+                '1' => diagnostic.set_bit(width - 1 - column),
+                _ => return Err((column + 1, c)),
+            }
+        }
+
+        Ok(diagnostic)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The bits in 64 bit words, the least significant word first.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn bit(&self, n: usize) -> usize {
+        ((self.words[n / 64] >> (n % 64)) & 1) as usize
+    }
+
+    pub fn set_bit(&mut self, n: usize) {
+        self.words[n / 64] |= 1 << (n % 64);
+    }
+
+    /// The numeric value, if the set bits fit into a `u64`.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.words.split_first() {
+            None => Some(0),
+            Some((&first, rest)) if rest.iter().all(|&word| word == 0) => Some(first),
+            _ => None,
+        }
+    }
+}
+
+/// Formats the diagnostic the same way as it appears in the report.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (0..self.width)
+            .rev()
+            .try_for_each(|n| write!(f, "{}", self.bit(n)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_bin_str() {
+        let diagnostic = Diagnostic::from_bin_str("10110").unwrap();
+
+        assert_eq!(diagnostic.width(), 5);
+        assert_eq!(diagnostic.to_u64(), Some(22));
+        assert_eq!(diagnostic.to_string(), "10110");
+        assert_eq!(Diagnostic::from_bin_str("10210"), Err((3, '2')));
+    }
+
+    #[test]
+    fn test_wider_than_64_bits() {
+        let bin_str = format!("1{}1", "0".repeat(98));
+        let diagnostic = Diagnostic::from_bin_str(&bin_str).unwrap();
+
+        assert_eq!(diagnostic.width(), 100);
+        assert_eq!(diagnostic.bit(0), 1);
+        assert_eq!(diagnostic.bit(50), 0);
+        assert_eq!(diagnostic.bit(99), 1);
+        assert_eq!(diagnostic.to_u64(), None);
+        assert_eq!(diagnostic.to_string(), bin_str);
+    }
+}
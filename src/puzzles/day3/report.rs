@@ -0,0 +1,228 @@
+use std::fmt;
+
+use super::{
+    calc_rate,
+    column_counts::ColumnCounts,
+    diagnostic::Diagnostic,
+    trie::{RatingError, Trie},
+    Bit, BitCriteria,
+};
+
+/// Number of remaining candidates listed per row of the elimination table.
+const MAX_LISTED_CANDIDATES: usize = 8;
+
+/// Everything that went into the results of a diagnostic report, so the
+/// answers can be checked by hand.
+#[derive(Debug)]
+pub struct DiagnosticReport {
+    pub counts: ColumnCounts,
+    pub gamma_rate: Diagnostic,
+    pub epsilon_rate: Diagnostic,
+    pub oxygen_generator: Result<RatingAudit, RatingError>,
+    pub co2_scrubber: Result<RatingAudit, RatingError>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RatingAudit {
+    pub rating: Diagnostic,
+    pub eliminations: Vec<Elimination>,
+}
+
+/// The candidates that remained after the criteria kept `kept` at `bit`.
+#[derive(Debug, PartialEq)]
+pub struct Elimination {
+    pub bit: usize,
+    pub zeros: usize,
+    pub ones: usize,
+    pub kept: Bit,
+    pub remaining: Vec<Diagnostic>,
+}
+
+impl DiagnosticReport {
+    pub fn new(diagnostic: &[Diagnostic], nr_of_bits: usize) -> DiagnosticReport {
+        let counts = ColumnCounts::count(diagnostic, nr_of_bits);
+        let trie = Trie::build(diagnostic, nr_of_bits);
+
+        DiagnosticReport {
+            gamma_rate: calc_rate(&counts, BitCriteria::GAMMA),
+            epsilon_rate: calc_rate(&counts, BitCriteria::EPSILON),
+            counts,
+            oxygen_generator: audit_rating(&trie, diagnostic, BitCriteria::OXYGEN_GENERATOR),
+            co2_scrubber: audit_rating(&trie, diagnostic, BitCriteria::CO2_SCRUBBER),
+        }
+    }
+}
+
+fn audit_rating(
+    trie: &Trie,
+    diagnostic: &[Diagnostic],
+    criteria: BitCriteria,
+) -> Result<RatingAudit, RatingError> {
+    let (rating, steps) = trie.rating_steps(criteria)?;
+    let mut candidates = diagnostic.to_vec();
+
+    let eliminations = steps
+        .into_iter()
+        .map(|step| {
+            candidates.retain(|line| line.bit(step.bit) == step.kept.index());
+
+            Elimination {
+                bit: step.bit,
+                zeros: step.zeros,
+                ones: step.ones,
+                kept: step.kept,
+                remaining: candidates.clone(),
+            }
+        })
+        .collect();
+
+    Ok(RatingAudit {
+        rating,
+        eliminations,
+    })
+}
+
+fn binary_and_decimal(value: &Diagnostic) -> String {
+    match value.to_u64() {
+        Some(decimal) => format!("{} ({})", value, decimal),
+        None => value.to_string(),
+    }
+}
+
+fn write_rating(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    audit: &Result<RatingAudit, RatingError>,
+) -> fmt::Result {
+    let audit = match audit {
+        Ok(audit) => audit,
+        Err(err) => return writeln!(f, "{}: {:?}", name, err),
+    };
+
+    writeln!(f, "{}: {}", name, binary_and_decimal(&audit.rating))?;
+    writeln!(
+        f,
+        "{:>6} | {:>6} | {:>6} | {:>4} | remaining",
+        "bit", "zeros", "ones", "kept"
+    )?;
+
+    for elimination in &audit.eliminations {
+        let listed: Vec<String> = elimination
+            .remaining
+            .iter()
+            .take(MAX_LISTED_CANDIDATES)
+            .map(|line| line.to_string())
+            .collect();
+        let more = elimination
+            .remaining
+            .len()
+            .saturating_sub(MAX_LISTED_CANDIDATES);
+
+        write!(
+            f,
+            "{:>6} | {:>6} | {:>6} | {:>4} | {}: {}",
+            elimination.bit,
+            elimination.zeros,
+            elimination.ones,
+            elimination.kept,
+            elimination.remaining.len(),
+            listed.join(", ")
+        )?;
+        if more > 0 {
+            write!(f, ", ... ({} more)", more)?;
+        }
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for DiagnosticReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>6} | {:>6} | {:>6} | {:>5} | {:>7}",
+            "column", "zeros", "ones", "gamma", "epsilon"
+        )?;
+        for n_bit in (0..self.counts.ones.len()).rev() {
+            writeln!(
+                f,
+                "{:>6} | {:>6} | {:>6} | {:>5} | {:>7}",
+                n_bit,
+                self.counts.zeros(n_bit),
+                self.counts.ones(n_bit),
+                self.gamma_rate.bit(n_bit),
+                self.epsilon_rate.bit(n_bit)
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "gamma rate: {}", binary_and_decimal(&self.gamma_rate))?;
+        writeln!(
+            f,
+            "epsilon rate: {}",
+            binary_and_decimal(&self.epsilon_rate)
+        )?;
+        writeln!(f)?;
+        write_rating(f, "oxygen generator rating", &self.oxygen_generator)?;
+        writeln!(f)?;
+        write_rating(f, "CO2 scrubber rating", &self.co2_scrubber)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{diagnostic, lines, parse_str_diagnostic};
+    use super::*;
+
+    #[test]
+    fn test_oxygen_generator_eliminations() {
+        let report = DiagnosticReport::new(&parse_str_diagnostic(&diagnostic()).unwrap(), 5);
+        let audit = report.oxygen_generator.unwrap();
+
+        assert_eq!(audit.rating.to_u64(), Some(23));
+        assert_eq!(audit.eliminations.len(), 5);
+        assert_eq!(
+            audit.eliminations[0],
+            Elimination {
+                bit: 4,
+                zeros: 5,
+                ones: 7,
+                kept: Bit::One,
+                remaining: parse_str_diagnostic(&lines(&[
+                    "11110", "10110", "10111", "10101", "11100", "10000", "11001"
+                ]))
+                .unwrap()
+            }
+        );
+        assert_eq!(
+            audit.eliminations[4].remaining,
+            parse_str_diagnostic(&lines(&["10111"])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_co2_scrubber_stops_when_one_candidate_remains() {
+        let report = DiagnosticReport::new(&parse_str_diagnostic(&diagnostic()).unwrap(), 5);
+        let audit = report.co2_scrubber.unwrap();
+
+        assert_eq!(audit.rating.to_u64(), Some(10));
+        assert_eq!(audit.eliminations.len(), 3);
+        assert_eq!(
+            audit.eliminations[2].remaining,
+            parse_str_diagnostic(&lines(&["01010"])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let table =
+            DiagnosticReport::new(&parse_str_diagnostic(&diagnostic()).unwrap(), 5).to_string();
+
+        assert!(table.contains("     4 |      5 |      7 |     1 |       0\n"));
+        assert!(table.contains("gamma rate: 10110 (22)\n"));
+        assert!(table.contains("epsilon rate: 01001 (9)\n"));
+        assert!(table.contains("oxygen generator rating: 10111 (23)\n"));
+        assert!(table.contains("     4 |      5 |      7 |    1 | 7: 11110, 10110, 10111, 10101, 11100, 10000, 11001\n"));
+    }
+}
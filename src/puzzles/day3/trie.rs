@@ -0,0 +1,204 @@
+use super::{diagnostic::Diagnostic, Bit, BitCriteria};
+
+#[derive(Debug, PartialEq)]
+pub enum RatingError {
+    EmptyReport,
+    /// The criteria selected a bit value none of the candidates have.
+    NoCandidates {
+        bit: usize,
+    },
+    /// More than one (identical) line remained after all bits were used.
+    Ambiguous {
+        candidates: usize,
+    },
+}
+
+/// A bit position where the criteria had to choose between the remaining
+/// lines.
+#[derive(Debug, PartialEq)]
+pub struct Step {
+    pub bit: usize,
+    pub zeros: usize,
+    pub ones: usize,
+    pub kept: Bit,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    count: usize,
+    children: [Option<usize>; 2],
+}
+
+/// Binary trie of the report lines, most significant bit first. Every node
+/// knows how many lines share its prefix, so the bit frequencies the rating
+/// criteria need are available without scanning the report again.
+#[derive(Debug)]
+pub struct Trie {
+    nodes: Vec<Node>,
+    width: usize,
+}
+
+impl Trie {
+    pub fn build(diagnostic: &[Diagnostic], width: usize) -> Trie {
+        let mut trie = Trie {
+            nodes: vec![Node::default()],
+            width,
+        };
+
+        for line in diagnostic {
+            let mut current = 0;
+            trie.nodes[current].count += 1;
+
+            for bit in (0..width).rev() {
+                let value = line.bit(bit);
+
+                current = match trie.nodes[current].children[value] {
+                    Some(child) => child,
+                    None => {
+                        trie.nodes.push(Node::default());
+                        let child = trie.nodes.len() - 1;
+                        trie.nodes[current].children[value] = Some(child);
+                        child
+                    }
+                };
+                trie.nodes[current].count += 1;
+            }
+        }
+
+        trie
+    }
+
+    fn count(&self, child: Option<usize>) -> usize {
+        child.map_or(0, |child| self.nodes[child].count)
+    }
+
+    pub fn rating(&self, criteria: BitCriteria) -> Result<Diagnostic, RatingError> {
+        self.rating_steps(criteria).map(|(rating, _)| rating)
+    }
+
+    /// Walks down the trie keeping the bit chosen by `criteria` from the
+    /// number of zeros and ones among the remaining lines. Once a single
+    /// line remains, its path is followed to the end. Besides the rating,
+    /// the choices made on the way are returned.
+    pub fn rating_steps(
+        &self,
+        criteria: BitCriteria,
+    ) -> Result<(Diagnostic, Vec<Step>), RatingError> {
+        if self.nodes[0].count == 0 {
+            return Err(RatingError::EmptyReport);
+        }
+
+        let mut rating = Diagnostic::zero(self.width);
+        let mut steps = vec![];
+        let mut current = 0;
+
+        for bit in (0..self.width).rev() {
+            let [zeros, ones] = self.nodes[current].children;
+
+            let value = if self.nodes[current].count == 1 {
+                if zeros.is_some() {
+                    Bit::Zero
+                } else {
+                    Bit::One
+                }
+            } else {
+                let (zeros, ones) = (self.count(zeros), self.count(ones));
+                let kept = criteria.select(zeros, ones);
+                steps.push(Step {
+                    bit,
+                    zeros,
+                    ones,
+                    kept,
+                });
+                kept
+            };
+
+            current = self.nodes[current].children[value.index()]
+                .ok_or(RatingError::NoCandidates { bit })?;
+
+            if value == Bit::One {
+                rating.set_bit(bit);
+            }
+        }
+
+        match self.nodes[current].count {
+            1 => Ok((rating, steps)),
+            candidates => Err(RatingError::Ambiguous { candidates }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Commonness;
+    use super::*;
+
+    fn trie(lines: &[&str]) -> Trie {
+        let diagnostic: Vec<Diagnostic> = lines
+            .iter()
+            .map(|line| Diagnostic::from_bin_str(line).unwrap())
+            .collect();
+
+        Trie::build(&diagnostic, lines.first().map_or(0, |line| line.len()))
+    }
+
+    #[test]
+    fn test_build_counts_prefixes() {
+        let trie = trie(&["101", "100", "001"]);
+
+        assert_eq!(trie.nodes[0].count, 3);
+        assert_eq!(trie.count(trie.nodes[0].children[1]), 2);
+        assert_eq!(trie.count(trie.nodes[0].children[0]), 1);
+    }
+
+    #[test]
+    fn test_rating_with_custom_tie() {
+        let least_common_tie_one = BitCriteria {
+            keep: Commonness::LeastCommon,
+            tie: Bit::One,
+        };
+        let (rating, steps) = trie(&["10", "01", "11", "00"])
+            .rating_steps(least_common_tie_one)
+            .unwrap();
+
+        assert_eq!(rating, Diagnostic::from_bin_str("11").unwrap());
+        assert_eq!(
+            steps,
+            vec![
+                Step {
+                    bit: 1,
+                    zeros: 2,
+                    ones: 2,
+                    kept: Bit::One
+                },
+                Step {
+                    bit: 0,
+                    zeros: 1,
+                    ones: 1,
+                    kept: Bit::One
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rating_errors() {
+        let always_zero = BitCriteria {
+            keep: Commonness::LeastCommon,
+            tie: Bit::Zero,
+        };
+
+        assert_eq!(
+            trie(&[]).rating(BitCriteria::GAMMA),
+            Err(RatingError::EmptyReport)
+        );
+        assert_eq!(
+            trie(&["110", "110"]).rating(BitCriteria::GAMMA),
+            Err(RatingError::Ambiguous { candidates: 2 })
+        );
+        assert_eq!(
+            trie(&["110", "111"]).rating(always_zero),
+            Err(RatingError::NoCandidates { bit: 2 })
+        );
+    }
+}
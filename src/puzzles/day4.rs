@@ -4,135 +4,789 @@
 /// demonstration.
 mod parser;
 
+/// Crafting inputs: the draw order making a chosen board win.
+mod draw_order;
+mod generator;
+mod odds;
+mod render;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
 use crate::file_utils::read_and_parse;
 
-type BoardState = Vec<(u32, bool)>;
+use parser::Day4Input;
 
-// TODO: memory consumption seems to be high, this needs to be investigated
-pub fn solve() {
-    let input = read_and_parse("data/day4.input", parser::parse).unwrap();
+/// Marked cells of a board, bit `i` is set when the `i`th cell (in row
+/// major order) is marked. Boards can have any size, so the bits are
+/// stored in as many words as needed.
+#[derive(Debug, Clone, PartialEq)]
+struct Marks(Vec<u64>);
 
-    let (first_winner, first_winner_index) =
-        find_first_winner(&input.bingo_boards, &input.numbers_drawn);
+impl Marks {
+    fn new(cells: usize) -> Marks {
+        Marks(vec![0; cells.div_ceil(64)])
+    }
 
-    let result_a = calc_board_score(
-        &first_winner,
-        input.numbers_drawn[first_winner_index] as usize,
-    );
-    println!("Day4 A result: {:?}", result_a);
+    fn set(&mut self, cell: usize) {
+        self.0[cell / 64] |= 1 << (cell % 64);
+    }
 
-    let (last_winner, last_winner_index) =
-        find_last_winner(&input.bingo_boards, &input.numbers_drawn);
+    fn is_set(&self, cell: usize) -> bool {
+        self.0[cell / 64] & (1 << (cell % 64)) != 0
+    }
 
-    let result_b = calc_board_score(
-        &last_winner,
-        input.numbers_drawn[last_winner_index] as usize,
-    );
-    println!("Day4 B result: {:?}", result_b);
+    fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.0.len() * 64).filter(|&cell| self.is_set(cell))
+    }
+
+    /// Whether every cell marked in `other` is marked here as well.
+    fn contains(&self, other: &Marks) -> bool {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .all(|(marked, other)| other & !marked == 0)
+    }
+}
+
+/// A pattern of marked cells that wins the game. Diagonals and X shapes
+/// only exist on square boards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WinRule {
+    Rows,
+    Columns,
+    Diagonals,
+    FourCorners,
+    X,
+    Blackout,
+}
+
+/// The rules of the puzzle.
+const DEFAULT_WIN_RULES: [WinRule; 2] = [WinRule::Rows, WinRule::Columns];
+
+impl WinRule {
+    fn from_name(name: &str) -> Option<WinRule> {
+        match name {
+            "rows" => Some(WinRule::Rows),
+            "columns" => Some(WinRule::Columns),
+            "diagonals" => Some(WinRule::Diagonals),
+            "corners" => Some(WinRule::FourCorners),
+            "x" => Some(WinRule::X),
+            "blackout" => Some(WinRule::Blackout),
+            _ => None,
+        }
+    }
+
+    /// Parses a comma separated list of rule names, e.g. `rows,diagonals`.
+    fn parse_list(names: &str) -> Result<Vec<WinRule>, String> {
+        names
+            .split(',')
+            .map(|name| WinRule::from_name(name).ok_or(format!("Unknown win rule: {}", name)))
+            .collect()
+    }
+
+    fn masks(self, width: usize, height: usize) -> Vec<Marks> {
+        let mask = |cells: &mut dyn Iterator<Item = usize>| {
+            cells.fold(Marks::new(width * height), |mut mask, cell| {
+                mask.set(cell);
+                mask
+            })
+        };
+        let diagonal = || (0..width).map(|i| i * width + i);
+        let anti_diagonal = || (0..width).map(|i| i * width + width - 1 - i);
+
+        match self {
+            WinRule::Rows => (0..height)
+                .map(|row| mask(&mut (0..width).map(|column| row * width + column)))
+                .collect(),
+            WinRule::Columns => (0..width)
+                .map(|column| mask(&mut (0..height).map(|row| row * width + column)))
+                .collect(),
+            WinRule::Diagonals if width == height => {
+                vec![mask(&mut diagonal()), mask(&mut anti_diagonal())]
+            }
+            WinRule::X if width == height => vec![mask(&mut diagonal().chain(anti_diagonal()))],
+            WinRule::Diagonals | WinRule::X => vec![],
+            WinRule::FourCorners => vec![mask(
+                &mut [0, width - 1, (height - 1) * width, height * width - 1].into_iter(),
+            )],
+            WinRule::Blackout => vec![mask(&mut (0..width * height))],
+        }
+    }
 }
 
-fn initial_state(boards: &[Vec<u32>]) -> Vec<BoardState> {
-    boards
+fn calc_win_masks(rules: &[WinRule], width: usize, height: usize) -> Vec<Marks> {
+    rules
         .iter()
-        .map(|board| board.iter().map(|&n| (n, false)).collect())
+        .flat_map(|rule| rule.masks(width, height))
         .collect()
 }
 
-fn calc_board_score(board_state: &[(u32, bool)], last_number: usize) -> usize {
-    board_state.iter().fold(
-        0,
-        |acc, &(n, marked)| if marked { acc } else { acc + n as usize },
-    ) * last_number
+#[derive(Debug, Clone, PartialEq)]
+struct Board<'a> {
+    numbers: &'a [u32],
+    marked: Marks,
+}
+
+impl Board<'_> {
+    fn is_winner(&self, win_masks: &[Marks]) -> bool {
+        self.winning_mask(win_masks).is_some()
+    }
+
+    /// The first win pattern the marked cells complete.
+    fn winning_mask<'m>(&self, win_masks: &'m [Marks]) -> Option<&'m Marks> {
+        win_masks.iter().find(|mask| self.marked.contains(mask))
+    }
+
+    fn is_marked(&self, cell: usize) -> bool {
+        self.marked.is_set(cell)
+    }
 }
 
-fn find_first_winner(boards: &[Vec<u32>], numbers_drawn: &[u32]) -> (BoardState, usize) {
-    let mut state = initial_state(boards);
-    let mut index = 0;
+/// The state of every board, and where each number can be found, so a
+/// draw only touches the boards containing the number.
+struct Game<'a> {
+    boards: Vec<Board<'a>>,
+    index: HashMap<u32, Vec<(usize, usize)>>,
+    win_masks: Vec<Marks>,
+}
 
-    loop {
-        calc_new_states(&mut state, numbers_drawn, index);
+impl<'a> Game<'a> {
+    fn new(boards: &'a [Vec<u32>], width: usize, height: usize, rules: &[WinRule]) -> Game<'a> {
+        let mut index: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
 
-        if let Some(w) = state.iter().find(|b| is_winner(b)) {
-            break (w.clone(), index);
+        for (b, board) in boards.iter().enumerate() {
+            for (cell, &n) in board.iter().enumerate() {
+                index.entry(n).or_default().push((b, cell));
+            }
         }
 
-        index += 1;
+        Game {
+            boards: boards
+                .iter()
+                .map(|numbers| Board {
+                    numbers,
+                    marked: Marks::new(width * height),
+                })
+                .collect(),
+            index,
+            win_masks: calc_win_masks(rules, width, height),
+        }
+    }
+
+    /// Marks the number and returns the boards containing it, in order.
+    fn draw(&mut self, number: u32) -> Vec<usize> {
+        let mut touched: Vec<usize> = vec![];
+
+        for &(b, cell) in self.index.get(&number).into_iter().flatten() {
+            self.boards[b].marked.set(cell);
+            if touched.last() != Some(&b) {
+                touched.push(b);
+            }
+        }
+
+        touched
+    }
+
+    fn is_winner(&self, board: usize) -> bool {
+        self.boards[board].is_winner(&self.win_masks)
     }
 }
 
-fn find_last_winner(boards: &[Vec<u32>], numbers_drawn: &[u32]) -> (BoardState, usize) {
-    let mut state = initial_state(boards);
-    let mut index = 0;
+pub fn solve(args: &[String]) {
+    if args.first().map(String::as_str) == Some("generate") {
+        let (seed, boards, width, height) = match args[1..]
+            .iter()
+            .map(|arg| arg.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .as_deref()
+        {
+            Ok(&[seed, boards]) => (seed, boards, 5, 5),
+            Ok(&[seed, boards, width, height]) => (seed, boards, width, height),
+            _ => return println!("Usage: generate <seed> <boards> [<width> <height>]"),
+        };
+
+        return match generator::generate(seed as u64, boards, width, height) {
+            Some(input) => print!("{}", input),
+            None => println!(
+                "Boards of {}x{} do not fit below {}",
+                width,
+                height,
+                generator::NUMBER_RANGE
+            ),
+        };
+    }
+
+    let input = read_and_parse("data/day4.input", parser::parse).unwrap();
 
-    loop {
-        calc_new_states(&mut state, numbers_drawn, index);
+    let mut rules = DEFAULT_WIN_RULES.to_vec();
+    let mut show_ranking = false;
+    let mut draw_order_for = None;
+    let mut odds_games = None;
+    let mut show_style = None;
+    let mut step = false;
+    let mut options = args.iter();
 
-        if state.len() == 1 && is_winner(&state[0]) {
-            break (state[0].clone(), index);
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "rules" => match options.next().map(|names| WinRule::parse_list(names)) {
+                Some(Ok(parsed)) => rules = parsed,
+                Some(Err(err)) => return println!("{}", err),
+                None => return println!("Missing win rules"),
+            },
+            "ranking" => show_ranking = true,
+            "order" => match options.next().map(|board| board.parse::<usize>()) {
+                Some(Ok(board)) => draw_order_for = Some(board),
+                _ => return println!("Missing or invalid board index"),
+            },
+            "odds" => match options.next().map(|games| games.parse::<usize>()) {
+                Some(Ok(games)) => odds_games = Some(games),
+                _ => return println!("Missing or invalid number of games"),
+            },
+            "show" => show_style = Some(render::Style::Ansi),
+            "brackets" => show_style = Some(render::Style::Brackets),
+            "step" => step = true,
+            _ => return println!("Unknown option: {}", option),
         }
+    }
+
+    if let Some(target) = draw_order_for {
+        return match draw_order::find_draw_order(&input, target, &rules) {
+            Ok(order) => println!(
+                "{}",
+                order
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            Err(draw_order::DrawOrderError::InvalidBoard) => println!("No board {}", target),
+            Err(draw_order::DrawOrderError::CannotWinFirst) => {
+                println!("Board {} cannot win first", target)
+            }
+        };
+    }
+
+    if let Some(games) = odds_games {
+        return print_odds(&odds::estimate(&input, &rules, games, 0));
+    }
+
+    if let Some(style) = show_style {
+        return show_game(&input, &rules, style, step);
+    }
 
-        state.retain(|b| !is_winner(b));
+    if show_ranking {
+        return print_ranking(&play(&input, &rules));
+    }
 
-        index += 1;
+    match find_first_winner(&input, &rules) {
+        Ok((first_winner, first_winner_index)) => {
+            let result_a = calc_board_score(&first_winner, input.numbers_drawn[first_winner_index]);
+            println!("Day4 A result: {:?}", result_a);
+        }
+        Err(err) => println!("Day4 A result: {:?}", err),
     }
+
+    match find_last_winner(&input, &rules) {
+        Ok((last_winner, last_winner_index)) => {
+            let result_b = calc_board_score(&last_winner, input.numbers_drawn[last_winner_index]);
+            println!("Day4 B result: {:?}", result_b);
+        }
+        Err(err) => println!("Day4 B result: {:?}", err),
+    }
+}
+
+/// When and with what score a board won.
+#[derive(Debug, PartialEq)]
+pub struct Win {
+    pub draw: usize,
+    pub number: u32,
+    pub score: u128,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BoardResult {
+    pub board: usize,
+    pub win: Option<Win>,
 }
 
-fn calc_new_states(board_states: &mut [Vec<(u32, bool)>], numbers_drawn: &[u32], index: usize) {
-    for board in board_states {
-        for cell in board {
-            if cell.0 == numbers_drawn[index] {
-                cell.1 = true;
+/// Plays the whole game in a single simulation. Boards are returned in the
+/// order they won (boards winning on the same draw in board order),
+/// followed by the boards that never win.
+fn play(input: &Day4Input, rules: &[WinRule]) -> Vec<BoardResult> {
+    let nr_of_boards = input.bingo_boards.len();
+    let mut game = Game::new(&input.bingo_boards, input.width, input.height, rules);
+    let mut won = vec![false; nr_of_boards];
+    let mut results = Vec::with_capacity(nr_of_boards);
+
+    for (draw, &number) in input.numbers_drawn.iter().enumerate() {
+        if results.len() == nr_of_boards {
+            break;
+        }
+
+        for b in game.draw(number) {
+            if !won[b] && game.is_winner(b) {
+                won[b] = true;
+                results.push(BoardResult {
+                    board: b,
+                    win: Some(Win {
+                        draw,
+                        number,
+                        score: calc_board_score(&game.boards[b], number),
+                    }),
+                });
             }
         }
     }
+
+    results.extend(
+        (0..nr_of_boards)
+            .filter(|&b| !won[b])
+            .map(|board| BoardResult { board, win: None }),
+    );
+
+    results
+}
+
+fn print_ranking(results: &[BoardResult]) {
+    println!(
+        "{:>5} | {:>5} | {:>5} | {:>6} | {:>6}",
+        "rank", "board", "draw", "number", "score"
+    );
+
+    for (rank, result) in results.iter().enumerate() {
+        match &result.win {
+            Some(win) => println!(
+                "{:>5} | {:>5} | {:>5} | {:>6} | {:>6}",
+                rank + 1,
+                result.board,
+                win.draw,
+                win.number,
+                win.score
+            ),
+            None => println!("{:>5} | {:>5} | never won", "-", result.board),
+        }
+    }
 }
 
-fn is_winner(board_state: &[(u32, bool)]) -> bool {
-    let (row_marked, column_marked) =
-        (0..5).fold((false, false), |(row_marked, column_marked), i| {
-            let (current_row, current_column) =
-                (0..5).fold((true, true), |(row_marked, column_marked), j| {
-                    (
-                        row_marked && board_state[(i * 5) + j].1,
-                        column_marked && board_state[i + (j * 5)].1,
-                    )
-                });
+/// Prints the game draw by draw. When stepping, every draw waits for
+/// enter to be pressed.
+fn show_game(input: &Day4Input, rules: &[WinRule], style: render::Style, step: bool) {
+    let stdin = io::stdin();
+
+    for frame in render::frames(input, rules, style) {
+        println!("{}", frame);
+
+        if step && stdin.lock().read_line(&mut String::new()).unwrap_or(0) == 0 {
+            break;
+        }
+    }
+}
+
+fn print_odds(estimates: &[odds::WinEstimate]) {
+    println!("{:>5} | {:>9} | {:>9}", "board", "first win", "mean draw");
+
+    for estimate in estimates {
+        println!(
+            "{:>5} | {:>8.2}% | {:>9}",
+            estimate.board,
+            estimate.first_win_probability * 100.0,
+            estimate
+                .expected_win_draw
+                .map_or("never won".to_owned(), |draw| format!("{:.2}", draw))
+        );
+    }
+}
+
+/// Cells can hold any `u32`, so the score is computed in a type that
+/// cannot overflow.
+fn calc_board_score(board: &Board, last_number: u32) -> u128 {
+    board
+        .numbers
+        .iter()
+        .enumerate()
+        .filter(|&(cell, _)| !board.is_marked(cell))
+        .map(|(_, &n)| n as u128)
+        .sum::<u128>()
+        * last_number as u128
+}
+
+/// Why there is no winner to report once every number has been drawn.
+#[derive(Debug, PartialEq)]
+pub enum NoWinner {
+    NoBoardWon,
+    /// Some boards never won, so there is no last winner.
+    NeverWon {
+        boards: Vec<usize>,
+    },
+}
 
-            (row_marked || current_row, column_marked || current_column)
-        });
+fn find_first_winner<'a>(
+    input: &'a Day4Input,
+    rules: &[WinRule],
+) -> Result<(Board<'a>, usize), NoWinner> {
+    let mut game = Game::new(&input.bingo_boards, input.width, input.height, rules);
 
-    row_marked || column_marked
+    for (index, &number) in input.numbers_drawn.iter().enumerate() {
+        let touched = game.draw(number);
+
+        if let Some(&w) = touched.iter().find(|&&b| game.is_winner(b)) {
+            return Ok((game.boards.swap_remove(w), index));
+        }
+    }
+
+    Err(NoWinner::NoBoardWon)
+}
+
+fn find_last_winner<'a>(
+    input: &'a Day4Input,
+    rules: &[WinRule],
+) -> Result<(Board<'a>, usize), NoWinner> {
+    let mut game = Game::new(&input.bingo_boards, input.width, input.height, rules);
+    let mut won = vec![false; input.bingo_boards.len()];
+    let mut remaining = input.bingo_boards.len();
+
+    for (index, &number) in input.numbers_drawn.iter().enumerate() {
+        for b in game.draw(number) {
+            if !won[b] && game.is_winner(b) {
+                won[b] = true;
+                remaining -= 1;
+
+                if remaining == 0 {
+                    return Ok((game.boards.swap_remove(b), index));
+                }
+            }
+        }
+    }
+
+    if remaining == input.bingo_boards.len() {
+        Err(NoWinner::NoBoardWon)
+    } else {
+        Err(NoWinner::NeverWon {
+            boards: (0..won.len()).filter(|&b| !won[b]).collect(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn example() -> Day4Input {
+        parser::parse(
+            "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+",
+        )
+        .unwrap()
+    }
+
+    fn board<'a>(numbers: &'a [u32], marked: &[usize]) -> Board<'a> {
+        let mut marks = Marks::new(numbers.len());
+        marked.iter().for_each(|&cell| marks.set(cell));
+
+        Board {
+            numbers,
+            marked: marks,
+        }
+    }
+
     #[test]
     fn test_is_winner_should_returns_true_when_full_row_is_marked() {
-        #[rustfmt::skip]
-        let state: Vec<(u32, bool)> = vec![
-            (21, false), (23, true),  (11, false), (54, false), (95, false),
-            (21, true),  (23, true),  (11, true),  (54, true),  (95, true),
-            (21, false), (23, false), (11, false), (54, false), (95, false),
-            (21, false), (23, false), (11, false), (54, false), (95, false),
-            (21, false), (23, true),  (11, false), (54, false), (95, false),
-        ];
+        let numbers: Vec<u32> = (0..25).collect();
+        let state = board(&numbers, &[1, 5, 6, 7, 8, 9, 21]);
 
-        assert!(is_winner(&state));
+        assert!(state.is_winner(&calc_win_masks(&DEFAULT_WIN_RULES, 5, 5)));
     }
 
     #[test]
     fn test_is_winner_should_returns_true_when_full_column_is_marked() {
-        #[rustfmt::skip]
-        let state: Vec<(u32, bool)> = vec![
-            (21, false), (23, true), (11, false), (54, false), (95, false),
-            (21, false), (23, true), (11, false), (54, false), (95, false),
-            (21, false), (23, true), (11, false), (54, false), (95, false),
-            (21, false), (23, true), (11, false), (54, false), (95, false),
-            (21, false), (23, true), (11, false), (54, false), (95, false),
-        ];
-
-        assert!(is_winner(&state));
+        let numbers: Vec<u32> = (0..25).collect();
+        let state = board(&numbers, &[1, 6, 11, 16, 21]);
+
+        assert!(state.is_winner(&calc_win_masks(&DEFAULT_WIN_RULES, 5, 5)));
+    }
+
+    #[test]
+    fn test_is_winner_should_returns_false_for_a_wrapping_line() {
+        let numbers: Vec<u32> = (0..25).collect();
+        let state = board(&numbers, &[3, 4, 5, 6, 7]);
+
+        assert!(!state.is_winner(&calc_win_masks(&DEFAULT_WIN_RULES, 5, 5)));
+    }
+
+    #[test]
+    fn test_is_winner_on_a_non_square_board() {
+        let numbers: Vec<u32> = (0..6).collect();
+        let win_masks = calc_win_masks(&DEFAULT_WIN_RULES, 3, 2);
+
+        assert!(board(&numbers, &[3, 4, 5]).is_winner(&win_masks));
+        assert!(board(&numbers, &[1, 4]).is_winner(&win_masks));
+        assert!(!board(&numbers, &[0, 1, 5]).is_winner(&win_masks));
+    }
+
+    #[test]
+    fn test_win_masks_beyond_64_cells() {
+        let numbers: Vec<u32> = (0..81).collect();
+        let last_column: Vec<usize> = (0..9).map(|row| row * 9 + 8).collect();
+
+        assert!(board(&numbers, &last_column).is_winner(&calc_win_masks(&DEFAULT_WIN_RULES, 9, 9)));
+        assert!(
+            !board(&numbers, &last_column[1..]).is_winner(&calc_win_masks(
+                &DEFAULT_WIN_RULES,
+                9,
+                9
+            ))
+        );
+    }
+
+    #[test]
+    fn test_draw_only_touches_boards_with_the_number() {
+        let input = example();
+        let mut game = Game::new(
+            &input.bingo_boards,
+            input.width,
+            input.height,
+            &DEFAULT_WIN_RULES,
+        );
+
+        assert_eq!(game.draw(26), vec![2]);
+        assert_eq!(game.draw(7), vec![0, 1, 2]);
+        assert!(game.boards[2].is_marked(24));
+        assert!(game.draw(99).is_empty());
+    }
+
+    #[test]
+    fn test_example() {
+        let input = example();
+
+        let (first, first_index) = find_first_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(
+            calc_board_score(&first, input.numbers_drawn[first_index]),
+            4512
+        );
+
+        let (last, last_index) = find_last_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(
+            calc_board_score(&last, input.numbers_drawn[last_index]),
+            1924
+        );
+    }
+
+    #[test]
+    fn test_3x3_boards() {
+        let input = parser::parse(
+            "2,5,8,12,13
+
+ 1  2  3
+ 4  5  6
+ 7  8  9
+
+ 9 10 11
+12  5 13
+14 15 16
+",
+        )
+        .unwrap();
+
+        assert_eq!((input.width, input.height), (3, 3));
+
+        let (first, first_index) = find_first_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(first_index, 2);
+        assert_eq!(first.numbers, &input.bingo_boards[0][..]);
+        assert_eq!(calc_board_score(&first, 8), (1 + 3 + 4 + 6 + 7 + 9) * 8);
+
+        let (last, last_index) = find_last_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(last_index, 4);
+        assert_eq!(last.numbers, &input.bingo_boards[1][..]);
+    }
+
+    #[test]
+    fn test_7x7_boards() {
+        let board: String = (0..7)
+            .map(|row| {
+                (0..7)
+                    .map(|column| format!("{:2}", row * 7 + column))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    + "\n"
+            })
+            .collect();
+        let input = parser::parse(&format!("3,10,17,24,31,38,45\n\n{}", board)).unwrap();
+
+        assert_eq!((input.width, input.height), (7, 7));
+
+        let (winner, index) = find_first_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(index, 6);
+        assert_eq!(
+            calc_board_score(&winner, 45),
+            ((0..49).sum::<u128>() - (3 + 10 + 17 + 24 + 31 + 38 + 45)) * 45
+        );
+    }
+
+    #[test]
+    fn test_win_rules() {
+        let numbers: Vec<u32> = (0..25).collect();
+        let wins = |rules: &[WinRule], marked: &[usize]| {
+            board(&numbers, marked).is_winner(&calc_win_masks(rules, 5, 5))
+        };
+        let diagonal = [0, 6, 12, 18, 24];
+        let anti_diagonal = [4, 8, 12, 16, 20];
+        let corners = [0, 4, 20, 24];
+
+        assert!(!wins(&DEFAULT_WIN_RULES, &diagonal));
+        assert!(wins(&[WinRule::Diagonals], &diagonal));
+        assert!(wins(&[WinRule::Diagonals], &anti_diagonal));
+        assert!(wins(&[WinRule::FourCorners], &corners));
+        assert!(!wins(&[WinRule::FourCorners], &corners[1..]));
+        assert!(!wins(&[WinRule::X], &diagonal));
+        assert!(wins(&[WinRule::X], &[diagonal, anti_diagonal].concat()));
+        assert!(!wins(&[WinRule::Blackout], &(0..24).collect::<Vec<_>>()));
+        assert!(wins(&[WinRule::Blackout], &(0..25).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn test_diagonals_need_a_square_board() {
+        assert!(WinRule::Diagonals.masks(3, 2).is_empty());
+        assert!(WinRule::X.masks(3, 2).is_empty());
+    }
+
+    #[test]
+    fn test_parse_win_rules() {
+        assert_eq!(
+            WinRule::parse_list("rows,diagonals,corners"),
+            Ok(vec![
+                WinRule::Rows,
+                WinRule::Diagonals,
+                WinRule::FourCorners
+            ])
+        );
+        assert!(WinRule::parse_list("rows,zigzag").is_err());
+    }
+
+    #[test]
+    fn test_find_first_winner_with_diagonals() {
+        let input = parser::parse("1,5,9,2,3\n\n 1  2  3\n 4  5  6\n 7  8  9\n").unwrap();
+        let rules = [WinRule::Rows, WinRule::Columns, WinRule::Diagonals];
+
+        assert_eq!(find_first_winner(&input, &DEFAULT_WIN_RULES).unwrap().1, 4);
+        assert_eq!(find_first_winner(&input, &rules).unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_play_answers_both_parts() {
+        let input = example();
+        let results = play(&input, &DEFAULT_WIN_RULES);
+
+        assert_eq!(
+            results,
+            vec![
+                BoardResult {
+                    board: 2,
+                    win: Some(Win {
+                        draw: 11,
+                        number: 24,
+                        score: 4512
+                    })
+                },
+                BoardResult {
+                    board: 0,
+                    win: Some(Win {
+                        draw: 13,
+                        number: 16,
+                        score: 2192
+                    })
+                },
+                BoardResult {
+                    board: 1,
+                    win: Some(Win {
+                        draw: 14,
+                        number: 13,
+                        score: 1924
+                    })
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_play_lists_boards_that_never_win() {
+        let input = parser::parse("1,2,3\n\n 1  2\n 4  5\n\n 7  8\n 9 10\n").unwrap();
+        let results = play(&input, &DEFAULT_WIN_RULES);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].board, 0);
+        assert_eq!(
+            results[0].win,
+            Some(Win {
+                draw: 1,
+                number: 2,
+                score: (4 + 5) * 2
+            })
+        );
+        assert_eq!(
+            results[1],
+            BoardResult {
+                board: 1,
+                win: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_board_wins_after_all_draws() {
+        let input = parser::parse("1,5,9\n\n 1  2\n 4  5\n\n 7  8\n 9 10\n").unwrap();
+
+        assert_eq!(
+            find_first_winner(&input, &DEFAULT_WIN_RULES),
+            Err(NoWinner::NoBoardWon)
+        );
+        assert_eq!(
+            find_last_winner(&input, &DEFAULT_WIN_RULES),
+            Err(NoWinner::NoBoardWon)
+        );
+    }
+
+    #[test]
+    fn test_no_last_winner_when_a_board_never_wins() {
+        let input =
+            parser::parse("1,2,7,8\n\n 1  2\n 4  5\n\n 3  6\n 9 10\n\n 7  8\n 0 11\n").unwrap();
+
+        assert_eq!(
+            find_first_winner(&input, &DEFAULT_WIN_RULES).map(|(_, index)| index),
+            Ok(1)
+        );
+        assert_eq!(
+            find_last_winner(&input, &DEFAULT_WIN_RULES),
+            Err(NoWinner::NeverWon { boards: vec![1] })
+        );
+    }
+
+    #[test]
+    fn test_score_of_boards_with_large_numbers() {
+        let input = parser::parse("4294967295\n\n4294967295\n4294967294\n").unwrap();
+
+        let (winner, index) = find_first_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(
+            calc_board_score(&winner, input.numbers_drawn[index]),
+            4294967294 * 4294967295
+        );
     }
 }
@@ -0,0 +1,191 @@
+use std::collections::BTreeSet;
+
+use super::{calc_win_masks, parser::Day4Input, Game, WinRule};
+
+#[derive(Debug, PartialEq)]
+pub enum DrawOrderError {
+    /// There is no board with the given index.
+    InvalidBoard,
+    /// The board loses or ties with a lower board in every order.
+    CannotWinFirst,
+}
+
+/// Finds an order of the numbers present on the boards in which `target` is
+/// the first winner, as `find_first_winner` reports it: boards winning on
+/// the same draw are decided by the lowest board index.
+///
+/// If such an order exists, one starting with the numbers of a single win
+/// pattern of the target works as well. Let `last` be the number completing
+/// the pattern: no board may win with the other numbers of the pattern, and
+/// no lower board may win once `last` is drawn too. Those sets are subsets
+/// of what was drawn in the original order, so any board winning with them
+/// would have won there as well. Checking every pattern and every `last` is
+/// therefore enough to prove that no order exists.
+pub fn find_draw_order(
+    input: &Day4Input,
+    target: usize,
+    rules: &[WinRule],
+) -> Result<Vec<u32>, DrawOrderError> {
+    let board = input
+        .bingo_boards
+        .get(target)
+        .ok_or(DrawOrderError::InvalidBoard)?;
+    let win_masks = calc_win_masks(rules, input.width, input.height);
+
+    let pattern = win_masks
+        .iter()
+        .find_map(|mask| {
+            let mut pattern: Vec<u32> = mask.cells().map(|cell| board[cell]).collect();
+            pattern.sort_unstable();
+            pattern.dedup();
+
+            // preferably the largest number last, so the pattern stays sorted
+            let last = (0..pattern.len())
+                .rev()
+                .find(|&last| wins_first(input, rules, &pattern, last, target))?;
+            let n = pattern.remove(last);
+            pattern.push(n);
+
+            Some(pattern)
+        })
+        .ok_or(DrawOrderError::CannotWinFirst)?;
+
+    let rest: BTreeSet<u32> = input
+        .bingo_boards
+        .iter()
+        .flatten()
+        .filter(|n| !pattern.contains(n))
+        .copied()
+        .collect();
+
+    Ok(pattern.into_iter().chain(rest).collect())
+}
+
+/// Whether drawing `pattern` with `pattern[last]` drawn last makes
+/// `target` the first winner, with no board winning before that draw.
+fn wins_first(
+    input: &Day4Input,
+    rules: &[WinRule],
+    pattern: &[u32],
+    last: usize,
+    target: usize,
+) -> bool {
+    let mut game = Game::new(&input.bingo_boards, input.width, input.height, rules);
+
+    for (i, &n) in pattern.iter().enumerate() {
+        if i != last {
+            game.draw(n);
+        }
+    }
+
+    if (0..input.bingo_boards.len()).any(|b| game.is_winner(b)) {
+        return false;
+    }
+
+    // the same tie break as find_first_winner
+    let touched = game.draw(pattern[last]);
+    touched.into_iter().find(|&b| game.is_winner(b)) == Some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{find_first_winner, parser, DEFAULT_WIN_RULES};
+    use super::*;
+
+    fn with_draws(input: &Day4Input, numbers_drawn: Vec<u32>) -> Day4Input {
+        Day4Input {
+            numbers_drawn,
+            bingo_boards: input.bingo_boards.clone(),
+            width: input.width,
+            height: input.height,
+        }
+    }
+
+    #[test]
+    fn test_every_example_board_can_win_first() {
+        let input = parser::parse(
+            "1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+",
+        )
+        .unwrap();
+
+        for target in 0..3 {
+            let order = find_draw_order(&input, target, &DEFAULT_WIN_RULES).unwrap();
+            let crafted = with_draws(&input, order.clone());
+
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            assert_eq!(sorted.len(), order.len());
+            assert_eq!(sorted, (0..=26).collect::<Vec<u32>>());
+
+            let (winner, _) = find_first_winner(&crafted, &DEFAULT_WIN_RULES).unwrap();
+            assert_eq!(winner.numbers, &input.bingo_boards[target][..]);
+        }
+    }
+
+    #[test]
+    fn test_ties_go_to_the_lowest_board() {
+        // the second board is the first one transposed
+        let input = parser::parse("1\n\n 1  2\n 3  4\n\n 1  3\n 2  4\n").unwrap();
+
+        assert_eq!(
+            find_draw_order(&input, 0, &DEFAULT_WIN_RULES),
+            Ok(vec![1, 2, 3, 4])
+        );
+        assert_eq!(
+            find_draw_order(&input, 1, &DEFAULT_WIN_RULES),
+            Err(DrawOrderError::CannotWinFirst)
+        );
+        assert_eq!(
+            find_draw_order(&input, 1, &[WinRule::FourCorners]),
+            Err(DrawOrderError::CannotWinFirst)
+        );
+
+        let crafted = with_draws(&input, vec![1, 2, 3, 4]);
+        let (winner, _) = find_first_winner(&crafted, &DEFAULT_WIN_RULES).unwrap();
+        assert_eq!(winner.numbers, &input.bingo_boards[0][..]);
+    }
+
+    #[test]
+    fn test_win_rules_change_the_outcome() {
+        let input = parser::parse("1\n\n 1  5\n 6  4\n\n 2  7\n 8  3\n\n 1  2\n 3  4\n").unwrap();
+
+        assert_eq!(
+            find_draw_order(&input, 2, &[WinRule::Diagonals]),
+            Err(DrawOrderError::CannotWinFirst)
+        );
+        assert_eq!(
+            find_draw_order(&input, 2, &DEFAULT_WIN_RULES),
+            Ok(vec![1, 2, 3, 4, 5, 6, 7, 8])
+        );
+    }
+
+    #[test]
+    fn test_invalid_board() {
+        let input = parser::parse("1\n\n 1  2\n 3  4\n").unwrap();
+
+        assert_eq!(
+            find_draw_order(&input, 1, &DEFAULT_WIN_RULES),
+            Err(DrawOrderError::InvalidBoard)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::rng::Rng;
+
+/// Numbers on generated boards and draws are below this value.
+pub const NUMBER_RANGE: u32 = 100;
+
+/// Generates a day4 input: every number below [`NUMBER_RANGE`] drawn once
+/// in a random order, followed by `boards` boards of `width`x`height`
+/// distinct numbers. Returns `None` when a board has more cells than there
+/// are numbers to fill it with.
+pub fn generate(seed: u64, boards: usize, width: usize, height: usize) -> Option<String> {
+    let cells = width.checked_mul(height)?;
+    if cells == 0 || cells > NUMBER_RANGE as usize {
+        return None;
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut numbers: Vec<u32> = (0..NUMBER_RANGE).collect();
+
+    rng.shuffle(&mut numbers);
+    let mut input = numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    input.push('\n');
+
+    for _ in 0..boards {
+        rng.shuffle(&mut numbers);
+        input.push('\n');
+
+        for row in numbers[..cells].chunks(width) {
+            let cells: Vec<String> = row.iter().map(|n| format!("{:>2}", n)).collect();
+            input.push_str(&cells.join(" "));
+            input.push('\n');
+        }
+    }
+
+    Some(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::super::{find_last_winner, parser, DEFAULT_WIN_RULES};
+    use super::*;
+
+    #[test]
+    fn test_generated_input_parses() {
+        let input = parser::parse(&generate(7, 20, 5, 5).unwrap()).unwrap();
+
+        let mut drawn = input.numbers_drawn.clone();
+        drawn.sort_unstable();
+        assert_eq!(drawn, (0..NUMBER_RANGE).collect::<Vec<_>>());
+        assert_eq!(
+            (input.bingo_boards.len(), input.width, input.height),
+            (20, 5, 5)
+        );
+
+        for board in input.bingo_boards {
+            let mut numbers = board.clone();
+            numbers.sort_unstable();
+            numbers.dedup();
+            assert_eq!(numbers.len(), 25);
+        }
+    }
+
+    #[test]
+    fn test_generate_is_reproducible() {
+        assert_eq!(generate(42, 3, 4, 6), generate(42, 3, 4, 6));
+        assert_ne!(generate(42, 3, 4, 6), generate(43, 3, 4, 6));
+        assert_eq!(generate(0, 1, 11, 10), None);
+        assert_eq!(generate(0, 1, usize::MAX, 2), None);
+    }
+
+    /// The draw on which each board completes a row or a column, found from
+    /// the position of its numbers in the draw order.
+    fn naive_win_draws(input: &parser::Day4Input) -> Vec<usize> {
+        let mut drawn_at = vec![usize::MAX; NUMBER_RANGE as usize];
+        for (draw, &n) in input.numbers_drawn.iter().enumerate() {
+            drawn_at[n as usize] = draw;
+        }
+
+        input
+            .bingo_boards
+            .iter()
+            .map(|board| {
+                let rows = board
+                    .chunks(input.width)
+                    .map(|row| row.iter().map(|&n| drawn_at[n as usize]).max().unwrap());
+                let columns = (0..input.width).map(|column| {
+                    (0..input.height)
+                        .map(|row| drawn_at[board[row * input.width + column] as usize])
+                        .max()
+                        .unwrap()
+                });
+                rows.chain(columns).min().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_last_winner_on_generated_inputs() {
+        for seed in 0..20 {
+            let input = parser::parse(&generate(seed, 100, 5, 5).unwrap()).unwrap();
+            let win_draws = naive_win_draws(&input);
+
+            let (board, draw) = find_last_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+            let b = input
+                .bingo_boards
+                .iter()
+                .position(|numbers| &numbers[..] == board.numbers)
+                .unwrap();
+
+            assert_eq!(draw, *win_draws.iter().max().unwrap(), "seed {}", seed);
+            assert_eq!(win_draws[b], draw, "seed {}", seed);
+        }
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_find_last_winner_thousands_of_boards() {
+        let input = parser::parse(&generate(1, 10_000, 5, 5).unwrap()).unwrap();
+
+        let start = Instant::now();
+        let (_, draw) = find_last_winner(&input, &DEFAULT_WIN_RULES).unwrap();
+
+        println!(
+            "10k boards: last winner on draw {} in {:?}",
+            draw,
+            start.elapsed()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use super::{parser::Day4Input, play, WinRule};
+use crate::rng::Rng;
+
+/// How a board fares over many games with the draws shuffled.
+#[derive(Debug, PartialEq)]
+pub struct WinEstimate {
+    pub board: usize,
+    /// Boards winning on the same draw share the win.
+    pub first_win_probability: f64,
+    /// Mean index of the winning draw over the games the board won, `None`
+    /// if it never won.
+    pub expected_win_draw: Option<f64>,
+}
+
+/// Monte Carlo estimate of the odds of every board, playing `games` games
+/// with the numbers of the input drawn in a random order.
+pub fn estimate(input: &Day4Input, rules: &[WinRule], games: usize, seed: u64) -> Vec<WinEstimate> {
+    let nr_of_boards = input.bingo_boards.len();
+    let mut rng = Rng::new(seed);
+    let mut shuffled = Day4Input {
+        numbers_drawn: input.numbers_drawn.clone(),
+        bingo_boards: input.bingo_boards.clone(),
+        width: input.width,
+        height: input.height,
+    };
+
+    let mut first_wins = vec![0.0; nr_of_boards];
+    let mut win_draws = vec![(0usize, 0usize); nr_of_boards];
+
+    for _ in 0..games {
+        rng.shuffle(&mut shuffled.numbers_drawn);
+        let results = play(&shuffled, rules);
+
+        let first_draw = results.first().and_then(|result| result.win.as_ref());
+        if let Some(first_draw) = first_draw.map(|win| win.draw) {
+            let winners: Vec<usize> = results
+                .iter()
+                .take_while(|result| result.win.as_ref().map(|win| win.draw) == Some(first_draw))
+                .map(|result| result.board)
+                .collect();
+
+            for &b in &winners {
+                first_wins[b] += 1.0 / winners.len() as f64;
+            }
+        }
+
+        for result in &results {
+            if let Some(win) = &result.win {
+                win_draws[result.board].0 += win.draw;
+                win_draws[result.board].1 += 1;
+            }
+        }
+    }
+
+    (0..nr_of_boards)
+        .map(|board| {
+            let (draw_sum, wins) = win_draws[board];
+
+            WinEstimate {
+                board,
+                first_win_probability: first_wins[board] / games.max(1) as f64,
+                expected_win_draw: (wins > 0).then(|| draw_sum as f64 / wins as f64),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generator::generate, parser, DEFAULT_WIN_RULES};
+    use super::*;
+
+    #[test]
+    fn test_probabilities_add_up_and_are_reproducible() {
+        let input = parser::parse(&generate(3, 10, 5, 5).unwrap()).unwrap();
+
+        let estimates = estimate(&input, &DEFAULT_WIN_RULES, 200, 1);
+        let total: f64 = estimates.iter().map(|e| e.first_win_probability).sum();
+
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(estimates, estimate(&input, &DEFAULT_WIN_RULES, 200, 1));
+        assert!(estimates.iter().all(|e| e.expected_win_draw.is_some()));
+    }
+
+    #[test]
+    fn test_symmetric_boards_have_even_odds() {
+        let input = parser::parse("1,2\n\n 1\n\n 2\n").unwrap();
+
+        for estimate in estimate(&input, &DEFAULT_WIN_RULES, 2000, 7) {
+            assert!((estimate.first_win_probability - 0.5).abs() < 0.05);
+            assert!((estimate.expected_win_draw.unwrap() - 0.5).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_boards_always_winning_together_share_the_win() {
+        // the second board is the first one transposed
+        let input = parser::parse("1,2,3,4\n\n 1  2\n 3  4\n\n 1  3\n 2  4\n").unwrap();
+
+        assert!(estimate(&input, &DEFAULT_WIN_RULES, 100, 0)
+            .iter()
+            .all(|estimate| estimate.first_win_probability == 0.5));
+    }
+
+    #[test]
+    fn test_boards_that_cannot_win() {
+        let input = parser::parse("1,2\n\n 1  2\n\n 3  4\n").unwrap();
+
+        assert_eq!(
+            estimate(&input, &DEFAULT_WIN_RULES, 10, 0)[1],
+            WinEstimate {
+                board: 1,
+                first_win_probability: 0.0,
+                expected_win_draw: None,
+            }
+        );
+    }
+}
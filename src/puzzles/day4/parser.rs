@@ -1,17 +1,19 @@
-use nom::branch::alt;
-use nom::bytes::complete::{tag, take};
-use nom::character::complete::{char, digit1, line_ending};
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, line_ending, space0};
 
 use nom::combinator::map_res;
-use nom::combinator::{map, verify};
-use nom::multi::{count, separated_list1};
-use nom::sequence::{preceded, separated_pair, terminated};
+use nom::combinator::verify;
+use nom::multi::{many1, separated_list1};
+use nom::sequence::{pair, preceded, separated_pair, terminated};
 use nom::IResult;
 
+/// Every board has the same size, its cells are stored in row major order.
 #[derive(Debug, PartialEq)]
 pub struct Day4Input {
     pub numbers_drawn: Vec<u32>,
     pub bingo_boards: Vec<Vec<u32>>,
+    pub width: usize,
+    pub height: usize,
 }
 
 #[derive(Debug, PartialEq)]
@@ -26,10 +28,34 @@ pub fn parse(input: &str) -> Result<Day4Input, ParserError> {
         separated_list1(line_ending, bingo_board),
     )(input)
     {
-        Ok(("", (numbers_drawn, bingo_boards))) => Ok(Day4Input {
-            numbers_drawn,
-            bingo_boards,
-        }),
+        Ok(("", (numbers_drawn, bingo_boards))) => {
+            let (width, height) = (bingo_boards[0][0].len(), bingo_boards[0].len());
+
+            match bingo_boards
+                .iter()
+                .position(|board| board.len() != height || board[0].len() != width)
+            {
+                Some(b) => Err(ParserError {
+                    message: format!(
+                        "Board {} is {}x{}, expected {}x{}",
+                        b,
+                        bingo_boards[b][0].len(),
+                        bingo_boards[b].len(),
+                        width,
+                        height
+                    ),
+                }),
+                None => Ok(Day4Input {
+                    numbers_drawn,
+                    bingo_boards: bingo_boards
+                        .into_iter()
+                        .map(|rows| rows.into_iter().flatten().collect())
+                        .collect(),
+                    width,
+                    height,
+                }),
+            }
+        }
         Ok((remaining, _)) => Err(ParserError {
             message: format!("Remaining input: '{}'", remaining),
         }),
@@ -47,32 +73,21 @@ fn u32_parser(input: &str) -> IResult<&str, u32> {
     map_res(digit1, int_from_string)(input)
 }
 
+/// Cells are separated by any amount of whitespace, so numbers don't have
+/// to be aligned.
 fn bingo_number(input: &str) -> IResult<&str, u32> {
-    alt((
-        preceded(char(' '), map_res(take(1usize), int_from_string)),
-        map_res(take(2usize), int_from_string),
-    ))(input)
+    preceded(space0, u32_parser)(input)
 }
 
 fn numbers_drawn_line(input: &str) -> IResult<&str, Vec<u32>> {
-    terminated(
-        separated_list1(tag(","), verify(u32_parser, |n| *n < 100u32)),
-        line_ending,
-    )(input)
+    terminated(separated_list1(tag(","), u32_parser), line_ending)(input)
 }
 
-fn bingo_board(input: &str) -> IResult<&str, Vec<u32>> {
-    map(
-        count(
-            terminated(
-                verify(separated_list1(tag(" "), bingo_number), |l: &[u32]| {
-                    l.len() == 5
-                }),
-                line_ending,
-            ),
-            5,
-        ),
-        |lines| lines.into_iter().flatten().collect(),
+/// A board is a block of rows with the same number of cells.
+fn bingo_board(input: &str) -> IResult<&str, Vec<Vec<u32>>> {
+    verify(
+        many1(terminated(many1(bingo_number), pair(space0, line_ending))),
+        |rows: &[Vec<u32>]| rows.iter().all(|row| row.len() == rows[0].len()),
     )(input)
 }
 
@@ -104,8 +119,11 @@ mod tests {
             Ok((
                 "",
                 vec![
-                    12, 23, 7, 12, 34, 75, 54, 2, 34, 35, 19, 43, 32, 45, 67, 63, 71, 1, 37, 21,
-                    15, 72, 22, 94, 51
+                    vec![12, 23, 7, 12, 34],
+                    vec![75, 54, 2, 34, 35],
+                    vec![19, 43, 32, 45, 67],
+                    vec![63, 71, 1, 37, 21],
+                    vec![15, 72, 22, 94, 51],
                 ]
             ))
         )
@@ -167,8 +185,78 @@ mod tests {
                         14, 21, 17, 24, 4, 10, 16, 15, 9, 19, 18, 8, 23, 26, 20, 22, 11, 13, 6, 5,
                         2, 0, 12, 3, 7
                     ]
-                ]
+                ],
+                width: 5,
+                height: 5,
             })
         )
     }
+
+    #[test]
+    fn test_parse_detects_board_size() {
+        let input = "1,2,3
+
+ 1  2  3
+ 4  5  6
+
+ 7  8  9
+10 11 12
+";
+
+        assert_eq!(
+            parse(input),
+            Ok(Day4Input {
+                numbers_drawn: vec![1, 2, 3],
+                bingo_boards: vec![vec![1, 2, 3, 4, 5, 6], vec![7, 8, 9, 10, 11, 12]],
+                width: 3,
+                height: 2,
+            })
+        )
+    }
+
+    #[test]
+    fn test_parse_fails_when_board_sizes_differ() {
+        let input = "1,2,3
+
+ 1  2  3
+ 4  5  6
+
+ 7  8
+10 11
+";
+
+        assert!(parse(input).is_err())
+    }
+
+    #[test]
+    fn test_bingo_board_accepts_any_width_and_spacing() {
+        let board = "1 200  3
+  4294967295\t5 6
+7    8 90000  
+";
+
+        assert_eq!(
+            bingo_board(board),
+            Ok((
+                "",
+                vec![vec![1, 200, 3], vec![4294967295, 5, 6], vec![7, 8, 90000]]
+            ))
+        )
+    }
+
+    #[test]
+    fn test_bingo_board_fails_when_a_row_has_a_wrong_cell_count() {
+        assert!(bingo_board("1 2 3\n4 5\n6 7 8\n").is_err());
+        assert!(bingo_board("1 2\n3 4 5\n").is_err());
+    }
+
+    #[test]
+    fn test_numbers_out_of_u32_range_are_rejected() {
+        assert_eq!(
+            numbers_drawn_line("100,4294967295\n"),
+            Ok(("", vec![100, 4294967295]))
+        );
+        assert!(numbers_drawn_line("4294967296\n").is_err());
+        assert!(bingo_board("1 4294967296\n").is_err());
+    }
 }
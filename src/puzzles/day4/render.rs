@@ -0,0 +1,119 @@
+use super::{parser::Day4Input, Board, Game, Marks, WinRule};
+
+/// How marked cells and the winning line stand out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    /// Bold marked cells, bold and reversed winning line.
+    Ansi,
+    /// `[n]` for marked cells, `<n>` for the winning line, for terminals
+    /// without colors and for logs.
+    Brackets,
+}
+
+const BOLD: &str = "\x1b[1m";
+const BOLD_REVERSED: &str = "\x1b[1;7m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders the board as a grid. Cells of the win pattern the board
+/// completed are emphasized over plain marked cells.
+fn render_board(board: &Board, width: usize, win_masks: &[Marks], style: Style) -> String {
+    let winning = board.winning_mask(win_masks);
+    let digits = board
+        .numbers
+        .iter()
+        .map(|n| n.to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    board
+        .numbers
+        .chunks(width)
+        .enumerate()
+        .map(|(row, numbers)| {
+            numbers
+                .iter()
+                .enumerate()
+                .map(|(column, n)| {
+                    let cell = row * width + column;
+                    let in_win = winning.is_some_and(|mask| mask.is_set(cell));
+
+                    match (style, in_win, board.is_marked(cell)) {
+                        (Style::Ansi, true, _) => {
+                            format!(" {}{:>digits$}{} ", BOLD_REVERSED, n, RESET)
+                        }
+                        (Style::Ansi, false, true) => format!(" {}{:>digits$}{} ", BOLD, n, RESET),
+                        (Style::Brackets, true, _) => format!("<{:>digits$}>", n),
+                        (Style::Brackets, false, true) => format!("[{:>digits$}]", n),
+                        (_, false, false) => format!(" {:>digits$} ", n),
+                    }
+                })
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+/// Plays the game and renders one frame per draw: the number called, then
+/// every board containing it, noting the boards winning with it. Frames
+/// stop once every board has won.
+pub fn frames(input: &Day4Input, rules: &[WinRule], style: Style) -> Vec<String> {
+    let mut game = Game::new(&input.bingo_boards, input.width, input.height, rules);
+    let mut won = vec![false; input.bingo_boards.len()];
+    let mut frames = vec![];
+
+    for (draw, &number) in input.numbers_drawn.iter().enumerate() {
+        if won.iter().all(|&won| won) {
+            break;
+        }
+
+        let mut frame = format!("Draw {}: {}\n", draw, number);
+
+        for b in game.draw(number) {
+            let wins = !won[b] && game.is_winner(b);
+            won[b] |= wins;
+
+            frame += &format!("\nBoard {}{}\n", b, if wins { " wins!" } else { "" });
+            frame += &render_board(&game.boards[b], input.width, &game.win_masks, style);
+        }
+
+        frames.push(frame);
+    }
+
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{parser, DEFAULT_WIN_RULES};
+    use super::*;
+
+    fn input() -> Day4Input {
+        parser::parse("1,2,10,3\n\n 1  2\n10  4\n\n 5  6\n 3 10\n").unwrap()
+    }
+
+    #[test]
+    fn test_frames_with_brackets() {
+        let frames = frames(&input(), &DEFAULT_WIN_RULES, Style::Brackets);
+
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0], "Draw 0: 1\n\nBoard 0\n[ 1]  2 \n 10   4 \n");
+        assert_eq!(
+            frames[2],
+            "Draw 2: 10\n\nBoard 0\n< 1>< 2>\n[10]  4 \n\nBoard 1\n  5   6 \n  3 [10]\n"
+        );
+        assert_eq!(
+            frames[3],
+            "Draw 3: 3\n\nBoard 1 wins!\n  5   6 \n< 3><10>\n"
+        );
+    }
+
+    #[test]
+    fn test_frames_with_ansi() {
+        let frames = frames(&input(), &DEFAULT_WIN_RULES, Style::Ansi);
+
+        assert_eq!(
+            frames[1],
+            "Draw 1: 2\n\nBoard 0 wins!\n \x1b[1;7m 1\x1b[0m  \x1b[1;7m 2\x1b[0m \n 10   4 \n"
+        );
+    }
+}
@@ -30,20 +30,45 @@ impl Point {
 
 type Line = (Point, Point);
 
-pub fn solve() {
+/// Which lines are turned into points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineMode {
+    /// Horizontal and vertical lines only.
+    Straight,
+    /// Horizontal, vertical and 45° lines.
+    Diagonal,
+    /// Lines of any slope, rasterized with Bresenham's algorithm.
+    AnyAngle,
+}
+
+pub fn solve(args: &[String]) {
     let input = read_and_parse("data/day5.input", parser::parse).unwrap();
 
+    if args.first().map(String::as_str) == Some("any-angle") {
+        println!(
+            "Day5 any angle result: {}",
+            count_overlaps(&count_coverage(&input, LineMode::AnyAngle))
+        );
+        return;
+    }
+
     println!(
         "Day5 A result: {}",
-        count_overlaps(&count_coverage(&input, false))
+        count_overlaps(&count_coverage(&input, LineMode::Straight))
     );
     println!(
         "Day5 B result: {}",
-        count_overlaps(&count_coverage(&input, true))
+        count_overlaps(&count_coverage(&input, LineMode::Diagonal))
     );
 }
 
-fn line_to_points(line: &Line, count_diagonal: bool) -> Vec<Point> {
+fn line_to_points(line: &Line, mode: LineMode) -> Vec<Point> {
+    if mode == LineMode::AnyAngle {
+        return rasterize(line);
+    }
+
+    let count_diagonal = mode == LineMode::Diagonal;
+
     match line {
         &(Point { x: x1, y: y1 }, Point { x: x2, y: y2 }) if x1 == x2 => (min(y1, y2)
             ..max(y1, y2) + 1)
@@ -68,11 +93,48 @@ fn line_to_points(line: &Line, count_diagonal: bool) -> Vec<Point> {
     }
 }
 
-fn count_coverage(lines: &[Line], count_diagonal: bool) -> HashMap<Point, usize> {
+/// Bresenham's line algorithm, working in every octant. Points are
+/// returned from the end with the smaller x (then smaller y), so
+/// horizontal, vertical and 45° lines come out as with the other modes.
+fn rasterize(line: &Line) -> Vec<Point> {
+    let (start, end) = if (line.0.x, line.0.y) <= (line.1.x, line.1.y) {
+        (&line.0, &line.1)
+    } else {
+        (&line.1, &line.0)
+    };
+    let (dx, dy) = end.diff(start);
+    let (dx, dy, sy) = (dx.abs(), -dy.abs(), dy.signum());
+    let (mut x, mut y) = (start.x as isize, start.y as isize);
+    let mut err = dx + dy;
+    let mut points = vec![];
+
+    loop {
+        points.push(Point {
+            x: x as usize,
+            y: y as usize,
+        });
+
+        if (x as usize, y as usize) == (end.x, end.y) {
+            return points;
+        }
+
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += 1;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+fn count_coverage(lines: &[Line], mode: LineMode) -> HashMap<Point, usize> {
     let mut count = HashMap::new();
 
     for line in lines {
-        for point in line_to_points(line, count_diagonal) {
+        for point in line_to_points(line, mode) {
             let c = count.entry(point).or_insert(0);
             *c += 1
         }
@@ -98,7 +160,7 @@ mod tests {
             Point { x: 1, y: 3 },
         ];
 
-        assert_eq!(line_to_points(&line, false), expected);
+        assert_eq!(line_to_points(&line, LineMode::Straight), expected);
     }
 
     #[test]
@@ -110,7 +172,7 @@ mod tests {
             Point { x: 9, y: 7 },
         ];
 
-        assert_eq!(line_to_points(&line, false), expected);
+        assert_eq!(line_to_points(&line, LineMode::Straight), expected);
     }
 
     #[test]
@@ -122,7 +184,7 @@ mod tests {
             Point { x: 3, y: 3 },
         ];
 
-        assert_eq!(line_to_points(&line, true), expected);
+        assert_eq!(line_to_points(&line, LineMode::Diagonal), expected);
     }
 
     #[test]
@@ -134,14 +196,68 @@ mod tests {
             Point { x: 9, y: 7 },
         ];
 
-        assert_eq!(line_to_points(&line, true), expected);
+        assert_eq!(line_to_points(&line, LineMode::Diagonal), expected);
     }
 
     #[test]
     fn test_line_to_points_non_horizontal_vertical_or_diagonal() {
         assert_eq!(
-            line_to_points(&(Point { x: 6, y: 4 }, Point { x: 2, y: 1 }), true),
+            line_to_points(
+                &(Point { x: 6, y: 4 }, Point { x: 2, y: 1 }),
+                LineMode::Diagonal
+            ),
             vec![]
         )
     }
+
+    #[test]
+    fn test_any_angle_matches_the_other_modes() {
+        let lines = [
+            (Point { x: 1, y: 1 }, Point { x: 1, y: 3 }),
+            (Point { x: 9, y: 7 }, Point { x: 7, y: 7 }),
+            (Point { x: 1, y: 1 }, Point { x: 3, y: 3 }),
+            (Point { x: 9, y: 7 }, Point { x: 7, y: 9 }),
+            (Point { x: 8, y: 0 }, Point { x: 0, y: 8 }),
+            (Point { x: 5, y: 5 }, Point { x: 5, y: 5 }),
+        ];
+
+        for line in &lines {
+            assert_eq!(
+                line_to_points(line, LineMode::AnyAngle),
+                line_to_points(line, LineMode::Diagonal)
+            );
+        }
+    }
+
+    #[test]
+    fn test_any_angle_line() {
+        let expected = vec![
+            Point { x: 2, y: 1 },
+            Point { x: 3, y: 2 },
+            Point { x: 4, y: 3 },
+            Point { x: 5, y: 3 },
+            Point { x: 6, y: 4 },
+        ];
+
+        assert_eq!(
+            line_to_points(
+                &(Point { x: 6, y: 4 }, Point { x: 2, y: 1 }),
+                LineMode::AnyAngle
+            ),
+            expected
+        );
+        assert_eq!(
+            line_to_points(
+                &(Point { x: 0, y: 4 }, Point { x: 1, y: 0 }),
+                LineMode::AnyAngle
+            ),
+            vec![
+                Point { x: 0, y: 4 },
+                Point { x: 0, y: 3 },
+                Point { x: 1, y: 2 },
+                Point { x: 1, y: 1 },
+                Point { x: 1, y: 0 },
+            ]
+        );
+    }
 }
@@ -44,7 +44,6 @@ fn line_parser(input: &str) -> IResult<&str, Line> {
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
 
@@ -1,6 +1,6 @@
 use crate::file_utils::read_and_parse;
 
-pub fn solve() {
+pub fn solve(_args: &[String]) {
     let input: Vec<usize> = read_and_parse("data/day6.input", |input| {
         input
             .trim()
@@ -0,0 +1,35 @@
+/// Seedable xorshift generator, good enough for generating test data and
+/// shuffling without pulling in a random crate.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        const MIX: u64 = 0x2545_f491_4f6c_dd1d;
+
+        // xorshift gets stuck on zero
+        match seed ^ MIX {
+            0 => Rng(MIX),
+            state => Rng(state),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..bound`; the modulo bias is negligible for the small
+    /// bounds used here.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}